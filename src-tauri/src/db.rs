@@ -1,9 +1,12 @@
 use parking_lot::Mutex;
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+pub const WATCH_DIR_COLORS: &[&str] = &["#6366f1", "#06b6d4", "#f59e0b", "#10b981", "#ec4899", "#8b5cf6"];
+pub const WATCH_MODES: &[&str] = &["auto", "native", "poll"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Clip {
@@ -11,15 +14,29 @@ pub struct Clip {
     pub filename: String,
     pub path: String,
     pub dir_source: String,
+    pub watch_dir_id: Option<String>,
     pub recorded_at: i64,
+    // where recorded_at came from: filename, metadata, filetime or manual
+    pub recorded_at_source: String,
     pub file_size: i64,
+    // unix seconds, compared with file_size on each scan to spot in-place rewrites
+    pub file_mtime: Option<i64>,
+    // size + head/tail hash, used to follow files that were moved or renamed
+    pub fingerprint: Option<String>,
     pub duration_secs: Option<f64>,
     pub width: Option<i32>,
     pub height: Option<i32>,
+    // grid-size thumbnail
     pub thumb_path: Option<String>,
+    // larger variant for the detail panel, None for thumbnails made before variants existed
+    pub thumb_detail_path: Option<String>,
+    // seconds into the clip the user picked for the thumbnail, None = automatic
+    pub cover_time: Option<f64>,
     pub description: String,
     pub tags: Vec<String>,
     pub starred: bool,
+    // online, offline (watch root unreachable) or missing (file gone, root still there)
+    pub status: String,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -58,11 +75,80 @@ pub struct Tag {
     pub clip_count: Option<i64>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternProfile {
+    pub id: String,
+    pub name: String,
+    pub regex: String,
+    pub timestamp_format: String,
+    pub builtin: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchDir {
+    pub id: String,
+    pub path: String,
+    pub label: String,
+    pub color: String,
+    // None = unlimited
+    pub max_depth: Option<u32>,
+    pub include_globs: Vec<String>,
+    pub exclude_globs: Vec<String>,
+    pub enabled: bool,
+    // None = default pattern profile
+    pub profile_id: Option<String>,
+    // IANA zone for filename timestamps, None = system zone
+    pub timezone: Option<String>,
+    // auto, native or poll. auto polls network/fuse mounts and anything native can't watch
+    pub watch_mode: String,
+    // seconds between polls, None = default
+    pub poll_interval_secs: Option<u32>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
 #[derive(Clone)]
 pub struct DbState {
     conn: Arc<Mutex<Connection>>,
 }
 
+// column order read by clip_from_row
+const CLIP_COLUMNS: &str = "id, filename, path, dir_source, recorded_at, file_size,
+    duration_secs, width, height, thumb_path, description,
+    starred, created_at, updated_at, recorded_at_source, watch_dir_id, fingerprint,
+    status, file_mtime, cover_time, thumb_detail_path";
+
+fn clip_from_row(row: &rusqlite::Row) -> rusqlite::Result<Clip> {
+    Ok(Clip {
+        id: row.get(0)?,
+        filename: row.get(1)?,
+        path: row.get(2)?,
+        dir_source: row.get(3)?,
+        watch_dir_id: row.get(15)?,
+        recorded_at: row.get(4)?,
+        recorded_at_source: row.get(14)?,
+        file_size: row.get(5)?,
+        file_mtime: row.get(18)?,
+        fingerprint: row.get(16)?,
+        duration_secs: row.get(6)?,
+        width: row.get(7)?,
+        height: row.get(8)?,
+        thumb_path: row.get(9)?,
+        thumb_detail_path: row.get(20)?,
+        cover_time: row.get(19)?,
+        description: row.get::<_, Option<String>>(10)?.unwrap_or_default(),
+        starred: row.get::<_, i32>(11)? != 0,
+        status: row.get(17)?,
+        tags: vec![], // filled by the caller
+        created_at: row.get(12)?,
+        updated_at: row.get(13)?,
+    })
+}
+
 impl DbState {
     pub fn new(path: PathBuf) -> Result<Self, String> {
         let conn = Connection::open(path).map_err(|e| e.to_string())?;
@@ -176,10 +262,152 @@ impl DbState {
             ).map_err(|e| e.to_string())?;
         }
 
+        if version < 3 {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS pattern_profiles (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    regex TEXT NOT NULL,
+                    timestamp_format TEXT NOT NULL DEFAULT '',
+                    builtin INTEGER NOT NULL DEFAULT 0,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );"
+            ).map_err(|e| e.to_string())?;
+        }
+
+        if version < 4 {
+            // existing rows were all parsed from OBS filenames
+            conn.execute_batch(
+                "ALTER TABLE clips ADD COLUMN recorded_at_source TEXT NOT NULL DEFAULT 'filename';"
+            ).map_err(|e| e.to_string())?;
+        }
+
+        if version < 5 {
+            // filename timestamps used to be stored as if they were UTC, reinterpret
+            // them as system-local wall-clock time
+            let rows: Vec<(String, i64)> = {
+                let mut stmt = conn.prepare("SELECT id, recorded_at FROM clips WHERE recorded_at_source = 'filename'")
+                    .map_err(|e| e.to_string())?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .map_err(|e| e.to_string())?;
+                rows.filter_map(|r| r.ok()).collect()
+            };
+            conn.execute_batch("BEGIN").map_err(|e| e.to_string())?;
+            for (id, ts) in rows {
+                let naive = match chrono::DateTime::from_timestamp(ts, 0) {
+                    Some(dt) => dt.naive_utc(),
+                    None => continue,
+                };
+                conn.execute(
+                    "UPDATE clips SET recorded_at = ?1 WHERE id = ?2",
+                    params![crate::tz::local_to_utc(naive, None), id],
+                ).map_err(|e| e.to_string())?;
+            }
+            conn.execute_batch("COMMIT").map_err(|e| e.to_string())?;
+        }
+
+        if version < 6 {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS watch_dirs (
+                    id TEXT PRIMARY KEY,
+                    path TEXT NOT NULL UNIQUE,
+                    label TEXT NOT NULL,
+                    color TEXT NOT NULL DEFAULT '#6366f1',
+                    max_depth INTEGER,
+                    include_globs TEXT NOT NULL DEFAULT '[]',
+                    exclude_globs TEXT NOT NULL DEFAULT '[]',
+                    enabled INTEGER NOT NULL DEFAULT 1,
+                    profile_id TEXT REFERENCES pattern_profiles(id) ON DELETE SET NULL,
+                    timezone TEXT,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );
+
+                ALTER TABLE clips ADD COLUMN watch_dir_id TEXT REFERENCES watch_dirs(id) ON DELETE SET NULL;
+                CREATE INDEX IF NOT EXISTS idx_clips_watch_dir ON clips(watch_dir_id);"
+            ).map_err(|e| e.to_string())?;
+            migrate_watch_dirs(&conn)?;
+        }
+
+        if version < 7 {
+            conn.execute_batch(
+                "ALTER TABLE clips ADD COLUMN fingerprint TEXT;
+                CREATE INDEX IF NOT EXISTS idx_clips_fingerprint ON clips(fingerprint);"
+            ).map_err(|e| e.to_string())?;
+        }
+
+        if version < 8 {
+            conn.execute_batch(
+                "ALTER TABLE clips ADD COLUMN status TEXT NOT NULL DEFAULT 'online';
+                CREATE INDEX IF NOT EXISTS idx_clips_status ON clips(status);"
+            ).map_err(|e| e.to_string())?;
+        }
+
+        if version < 9 {
+            conn.execute_batch(
+                "ALTER TABLE clips ADD COLUMN file_mtime INTEGER;"
+            ).map_err(|e| e.to_string())?;
+        }
+
+        if version < 10 {
+            conn.execute_batch(
+                "ALTER TABLE clips ADD COLUMN content_hash TEXT;
+                CREATE INDEX IF NOT EXISTS idx_clips_size ON clips(file_size);
+
+                CREATE TABLE IF NOT EXISTS ignored_paths (
+                    path TEXT PRIMARY KEY,
+                    reason TEXT NOT NULL,
+                    created_at INTEGER NOT NULL
+                );"
+            ).map_err(|e| e.to_string())?;
+        }
+
+        if version < 11 {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS phashes (
+                    clip_id TEXT PRIMARY KEY REFERENCES clips(id) ON DELETE CASCADE,
+                    hashes BLOB NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );"
+            ).map_err(|e| e.to_string())?;
+        }
+
+        if version < 12 {
+            conn.execute_batch(
+                "ALTER TABLE watch_dirs ADD COLUMN watch_mode TEXT NOT NULL DEFAULT 'auto';
+                 ALTER TABLE watch_dirs ADD COLUMN poll_interval_secs INTEGER;"
+            ).map_err(|e| e.to_string())?;
+        }
+
+        if version < 13 {
+            conn.execute_batch("ALTER TABLE clips ADD COLUMN cover_time REAL;")
+                .map_err(|e| e.to_string())?;
+        }
+
+        if version < 14 {
+            // thumb_sig records the thumbnail settings the files were made with
+            conn.execute_batch(
+                "ALTER TABLE clips ADD COLUMN thumb_detail_path TEXT;
+                 ALTER TABLE clips ADD COLUMN thumb_sig TEXT;"
+            ).map_err(|e| e.to_string())?;
+        }
+
+        // (re)seed built-in pattern profiles so upgrades pick up changes
+        let now = chrono::Utc::now().timestamp();
+        for (id, name, regex, fmt) in crate::patterns::BUILTIN_PROFILES {
+            conn.execute(
+                "INSERT INTO pattern_profiles (id, name, regex, timestamp_format, builtin, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, 1, ?5, ?5)
+                 ON CONFLICT(id) DO UPDATE SET name = ?2, regex = ?3, timestamp_format = ?4, builtin = 1",
+                params![id, name, regex, fmt, now],
+            ).map_err(|e| e.to_string())?;
+        }
+
         // update schema version
         conn.execute(
-            "INSERT INTO app_meta (key, value) VALUES ('schema_version', '2')
-             ON CONFLICT(key) DO UPDATE SET value = '2'",
+            "INSERT INTO app_meta (key, value) VALUES ('schema_version', '14')
+             ON CONFLICT(key) DO UPDATE SET value = '14'",
             [],
         ).map_err(|e| e.to_string())?;
 
@@ -191,9 +419,9 @@ impl DbState {
     pub fn insert_clip(&self, clip: &Clip) -> Result<(), String> {
         let conn = self.conn.lock();
         conn.execute(
-            "INSERT OR IGNORE INTO clips (id, filename, path, dir_source, recorded_at, file_size, description, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-            params![clip.id, clip.filename, clip.path, clip.dir_source, clip.recorded_at, clip.file_size, clip.description, clip.created_at, clip.updated_at],
+            "INSERT OR IGNORE INTO clips (id, filename, path, dir_source, watch_dir_id, recorded_at, recorded_at_source, file_size, file_mtime, fingerprint, description, status, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![clip.id, clip.filename, clip.path, clip.dir_source, clip.watch_dir_id, clip.recorded_at, clip.recorded_at_source, clip.file_size, clip.file_mtime, clip.fingerprint, clip.description, clip.status, clip.created_at, clip.updated_at],
         ).map_err(|e| e.to_string())?;
         Ok(())
     }
@@ -215,32 +443,10 @@ impl DbState {
         }
 
         let mut stmt = conn.prepare(
-            "SELECT id, filename, path, dir_source, recorded_at, file_size,
-                    duration_secs, width, height, thumb_path, description,
-                    starred, created_at, updated_at
-             FROM clips ORDER BY recorded_at DESC"
+            &format!("SELECT {} FROM clips ORDER BY recorded_at DESC", CLIP_COLUMNS)
         ).map_err(|e| e.to_string())?;
 
-        let rows = stmt.query_map([], |row| {
-            let id: String = row.get(0)?;
-            Ok(Clip {
-                id,
-                filename: row.get(1)?,
-                path: row.get(2)?,
-                dir_source: row.get(3)?,
-                recorded_at: row.get(4)?,
-                file_size: row.get(5)?,
-                duration_secs: row.get(6)?,
-                width: row.get(7)?,
-                height: row.get(8)?,
-                thumb_path: row.get(9)?,
-                description: row.get::<_, Option<String>>(10)?.unwrap_or_default(),
-                starred: row.get::<_, i32>(11)? != 0,
-                tags: vec![], // filled below
-                created_at: row.get(12)?,
-                updated_at: row.get(13)?,
-            })
-        }).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], clip_from_row).map_err(|e| e.to_string())?;
 
         let mut clips: Vec<Clip> = rows.flatten().collect();
         for clip in &mut clips {
@@ -252,11 +458,81 @@ impl DbState {
         Ok(clips)
     }
 
-    pub fn update_clip_thumb(&self, clip_id: &str, thumb_path: &str) -> Result<(), String> {
+    pub fn get_clip(&self, clip_id: &str) -> Result<Option<Clip>, String> {
+        self.get_clip_where("id", clip_id)
+    }
+
+    pub fn get_clip_by_path(&self, path: &str) -> Result<Option<Clip>, String> {
+        self.get_clip_where("path", path)
+    }
+
+    // clips anywhere below a folder, for directory renames and deletes
+    pub fn get_clip_ids_under(&self, dir: &str) -> Result<Vec<String>, String> {
+        let prefix = format!("{}{}", dir.trim_end_matches(['/', '\\']), std::path::MAIN_SEPARATOR);
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT id FROM clips WHERE substr(path, 1, length(?1)) = ?1 ORDER BY path")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![prefix], |row| row.get(0)).map_err(|e| e.to_string())?;
+        Ok(rows.flatten().collect())
+    }
+
+    fn get_clip_where(&self, column: &str, value: &str) -> Result<Option<Clip>, String> {
+        let conn = self.conn.lock();
+        let clip = conn.query_row(
+            &format!("SELECT {} FROM clips WHERE {} = ?1", CLIP_COLUMNS, column),
+            params![value],
+            clip_from_row,
+        ).optional().map_err(|e| e.to_string())?;
+        let Some(mut clip) = clip else { return Ok(None) };
+
+        let mut stmt = conn.prepare("SELECT tag_id FROM clip_tags WHERE clip_id = ?1")
+            .map_err(|e| e.to_string())?;
+        clip.tags = stmt.query_map(params![clip.id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .flatten()
+            .collect();
+        Ok(Some(clip))
+    }
+
+    // fingerprint -> id of clips whose file has gone missing, candidates for a relink
+    pub fn get_missing_fingerprints(&self) -> Result<std::collections::HashMap<String, String>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT fingerprint, id FROM clips WHERE status = 'missing' AND fingerprint IS NOT NULL"
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        Ok(rows.flatten().collect())
+    }
+
+    pub fn update_clip_thumb(&self, clip_id: &str, thumb_path: &str, detail_path: Option<&str>, sig: Option<&str>) -> Result<(), String> {
         let conn = self.conn.lock();
         conn.execute(
-            "UPDATE clips SET thumb_path = ?1, updated_at = ?2 WHERE id = ?3",
-            params![thumb_path, chrono::Utc::now().timestamp(), clip_id],
+            "UPDATE clips SET thumb_path = ?1, thumb_detail_path = ?2, thumb_sig = ?3, updated_at = ?4 WHERE id = ?5",
+            params![thumb_path, detail_path, sig, chrono::Utc::now().timestamp(), clip_id],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // online clips whose thumbnails were made with other settings than `sig`
+    pub fn get_stale_thumbs(&self, sig: &str) -> Result<Vec<(String, String)>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, path FROM clips
+             WHERE status = 'online' AND thumb_path IS NOT NULL AND (thumb_sig IS NULL OR thumb_sig != ?1)
+             ORDER BY recorded_at DESC"
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![sig], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    // None goes back to automatic picking
+    pub fn set_clip_cover_time(&self, clip_id: &str, cover_time: Option<f64>) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE clips SET cover_time = ?1, updated_at = ?2 WHERE id = ?3",
+            params![cover_time, chrono::Utc::now().timestamp(), clip_id],
         ).map_err(|e| e.to_string())?;
         Ok(())
     }
@@ -279,6 +555,138 @@ impl DbState {
         Ok(())
     }
 
+    pub fn update_clip_fingerprint(&self, clip_id: &str, fingerprint: &str) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE clips SET fingerprint = ?1 WHERE id = ?2",
+            params![fingerprint, clip_id],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // record size/mtime without touching anything derived from the content
+    pub fn update_clip_file_stat(&self, clip_id: &str, file_size: i64, file_mtime: i64) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE clips SET file_size = ?1, file_mtime = ?2 WHERE id = ?3",
+            params![file_size, file_mtime, clip_id],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // the bytes changed but the media didn't (metadata rewrite): new stat and
+    // fingerprint, full hash recomputed on demand, probe results and thumbnail kept
+    pub fn update_clip_file_identity(&self, clip_id: &str, file_size: i64, file_mtime: i64, fingerprint: Option<&str>) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE clips SET file_size = ?1, file_mtime = ?2, fingerprint = ?3, content_hash = NULL WHERE id = ?4",
+            params![file_size, file_mtime, fingerprint, clip_id],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // the file was rewritten in place: store the new stat and drop everything derived
+    // from the old content (probe results, thumbnail, waveform, embedding)
+    pub fn invalidate_clip_content(&self, clip_id: &str, file_size: i64, file_mtime: i64, fingerprint: Option<&str>) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute_batch("BEGIN").map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE clips SET file_size = ?1, file_mtime = ?2, fingerprint = ?3, content_hash = NULL,
+                    duration_secs = NULL, width = NULL, height = NULL, thumb_path = NULL, thumb_detail_path = NULL,
+                    thumb_sig = NULL, cover_time = NULL,
+                    updated_at = ?4
+             WHERE id = ?5",
+            params![file_size, file_mtime, fingerprint, chrono::Utc::now().timestamp(), clip_id],
+        ).map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM waveforms WHERE clip_id = ?1", params![clip_id])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM embeddings WHERE clip_id = ?1", params![clip_id])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM phashes WHERE clip_id = ?1", params![clip_id])
+            .map_err(|e| e.to_string())?;
+        conn.execute_batch("COMMIT").map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // point an existing row at a file's new location; tags, stars, collections etc stay attached
+    pub fn relink_clip(&self, clip_id: &str, path: &str, filename: &str, watch_dir_id: Option<&str>, dir_source: &str) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE clips SET path = ?1, filename = ?2, watch_dir_id = ?3, dir_source = ?4, status = 'online', updated_at = ?5
+             WHERE id = ?6",
+            params![path, filename, watch_dir_id, dir_source, chrono::Utc::now().timestamp(), clip_id],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn set_clip_status(&self, clip_ids: &[String], status: &str) -> Result<(), String> {
+        let conn = self.conn.lock();
+        let now = chrono::Utc::now().timestamp();
+        conn.execute_batch("BEGIN").map_err(|e| e.to_string())?;
+        for clip_id in clip_ids {
+            conn.execute(
+                "UPDATE clips SET status = ?1, updated_at = ?2 WHERE id = ?3 AND status != ?1",
+                params![status, now, clip_id],
+            ).map_err(|e| e.to_string())?;
+        }
+        conn.execute_batch("COMMIT").map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // the only path that drops clips the scanner couldn't find
+    // returns the purged ids so their cached files can go too
+    pub fn purge_clips(&self, statuses: &[&str]) -> Result<Vec<String>, String> {
+        let conn = self.conn.lock();
+        let mut purged = vec![];
+        for status in statuses {
+            let mut stmt = conn.prepare("DELETE FROM clips WHERE status = ?1 RETURNING id")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt.query_map(params![status], |row| row.get(0)).map_err(|e| e.to_string())?;
+            purged.extend(rows.flatten());
+        }
+        Ok(purged)
+    }
+
+    pub fn set_recorded_at(&self, clip_ids: &[String], recorded_at: i64) -> Result<(), String> {
+        let conn = self.conn.lock();
+        let now = chrono::Utc::now().timestamp();
+        conn.execute_batch("BEGIN").map_err(|e| e.to_string())?;
+        for clip_id in clip_ids {
+            conn.execute(
+                "UPDATE clips SET recorded_at = ?1, recorded_at_source = 'manual', updated_at = ?2 WHERE id = ?3",
+                params![recorded_at, now, clip_id],
+            ).map_err(|e| e.to_string())?;
+        }
+        conn.execute_batch("COMMIT").map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // a re-derived date, as opposed to one the user set
+    pub fn update_recorded_at(&self, clip_id: &str, recorded_at: i64, source: &str) -> Result<(), String> {
+        let conn = self.conn.lock();
+        let now = chrono::Utc::now().timestamp();
+        conn.execute(
+            "UPDATE clips SET recorded_at = ?1, recorded_at_source = ?2, updated_at = ?3 WHERE id = ?4",
+            params![recorded_at, source, now, clip_id],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // move a batch of clips by a fixed offset (camera clock off by an hour etc)
+    pub fn shift_recorded_at(&self, clip_ids: &[String], offset_secs: i64) -> Result<(), String> {
+        let conn = self.conn.lock();
+        let now = chrono::Utc::now().timestamp();
+        conn.execute_batch("BEGIN").map_err(|e| e.to_string())?;
+        for clip_id in clip_ids {
+            conn.execute(
+                "UPDATE clips SET recorded_at = recorded_at + ?1, recorded_at_source = 'manual', updated_at = ?2 WHERE id = ?3",
+                params![offset_secs, now, clip_id],
+            ).map_err(|e| e.to_string())?;
+        }
+        conn.execute_batch("COMMIT").map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     pub fn clip_exists_by_path(&self, path: &str) -> Result<bool, String> {
         let conn = self.conn.lock();
         let count: i64 = conn.query_row(
@@ -289,6 +697,108 @@ impl DbState {
         Ok(count > 0)
     }
 
+    // -- watch dirs --
+
+    pub fn get_watch_dirs(&self) -> Result<Vec<WatchDir>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, path, label, color, max_depth, include_globs, exclude_globs, enabled,
+                    profile_id, timezone, watch_mode, poll_interval_secs, created_at, updated_at
+             FROM watch_dirs ORDER BY created_at, path"
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| {
+            Ok(WatchDir {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                label: row.get(2)?,
+                color: row.get(3)?,
+                max_depth: row.get(4)?,
+                include_globs: serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default(),
+                exclude_globs: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+                enabled: row.get::<_, i32>(7)? != 0,
+                profile_id: row.get(8)?,
+                timezone: row.get(9)?,
+                watch_mode: row.get(10)?,
+                poll_interval_secs: row.get(11)?,
+                created_at: row.get(12)?,
+                updated_at: row.get(13)?,
+            })
+        }).map_err(|e| e.to_string())?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    pub fn create_watch_dir(&self, dir: &WatchDir) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO watch_dirs (id, path, label, color, max_depth, include_globs, exclude_globs, enabled,
+                                     profile_id, timezone, watch_mode, poll_interval_secs, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                dir.id, dir.path, dir.label, dir.color, dir.max_depth,
+                serde_json::to_string(&dir.include_globs).map_err(|e| e.to_string())?,
+                serde_json::to_string(&dir.exclude_globs).map_err(|e| e.to_string())?,
+                dir.enabled as i32, dir.profile_id, dir.timezone, dir.watch_mode, dir.poll_interval_secs,
+                dir.created_at, dir.updated_at,
+            ],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // path is fixed once created; everything else is editable
+    pub fn update_watch_dir(&self, dir: &WatchDir) -> Result<(), String> {
+        let conn = self.conn.lock();
+        let now = chrono::Utc::now().timestamp();
+        let old_zone: Option<String> = conn.query_row(
+            "SELECT timezone FROM watch_dirs WHERE id = ?1", params![dir.id], |row| row.get(0),
+        ).optional().map_err(|e| e.to_string())?.flatten();
+        conn.execute_batch("BEGIN").map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE watch_dirs SET label = ?1, color = ?2, max_depth = ?3, include_globs = ?4, exclude_globs = ?5,
+                                   enabled = ?6, profile_id = ?7, timezone = ?8, watch_mode = ?9,
+                                   poll_interval_secs = ?10, updated_at = ?11
+             WHERE id = ?12",
+            params![
+                dir.label, dir.color, dir.max_depth,
+                serde_json::to_string(&dir.include_globs).map_err(|e| e.to_string())?,
+                serde_json::to_string(&dir.exclude_globs).map_err(|e| e.to_string())?,
+                dir.enabled as i32, dir.profile_id, dir.timezone, dir.watch_mode, dir.poll_interval_secs,
+                now, dir.id,
+            ],
+        ).map_err(|e| e.to_string())?;
+        // keep the denormalized source label on clips in sync
+        conn.execute(
+            "UPDATE clips SET dir_source = ?1 WHERE watch_dir_id = ?2",
+            params![dir.label, dir.id],
+        ).map_err(|e| e.to_string())?;
+        if old_zone != dir.timezone {
+            if let Err(e) = rezone_filename_dates(&conn, &dir.id, old_zone.as_deref(), dir.timezone.as_deref()) {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        }
+        conn.execute_batch("COMMIT").map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn link_unowned_clips(&self) -> Result<usize, String> {
+        let conn = self.conn.lock();
+        link_unowned_clips(&conn)
+    }
+
+    // clips under the dir stay in the library as missing; only purge_clips drops rows
+    pub fn delete_watch_dir(&self, id: &str) -> Result<(), String> {
+        let conn = self.conn.lock();
+        let now = chrono::Utc::now().timestamp();
+        conn.execute_batch("BEGIN").map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE clips SET watch_dir_id = NULL, status = 'missing', updated_at = ?1 WHERE watch_dir_id = ?2",
+            params![now, id],
+        ).map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM watch_dirs WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+        conn.execute_batch("COMMIT").map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     // -- tags --
 
     pub fn get_all_tags(&self) -> Result<Vec<Tag>, String> {
@@ -322,6 +832,15 @@ impl DbState {
         Ok(Tag { id: id.to_string(), name: name.to_string(), color: color.to_string(), created_at: now, clip_count: Some(0) })
     }
 
+    pub fn get_tag_clip_ids(&self, tag_id: &str) -> Result<Vec<String>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT clip_id FROM clip_tags WHERE tag_id = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![tag_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
     pub fn delete_tag(&self, id: &str) -> Result<(), String> {
         let conn = self.conn.lock();
         conn.execute("DELETE FROM tags WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
@@ -355,6 +874,108 @@ impl DbState {
         Ok(())
     }
 
+    // -- duplicates --
+
+    // full-file hashes computed so far, keyed by clip id
+    pub fn get_content_hashes(&self) -> Result<std::collections::HashMap<String, String>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT id, content_hash FROM clips WHERE content_hash IS NOT NULL")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| e.to_string())?;
+        Ok(rows.flatten().collect())
+    }
+
+    pub fn update_clip_content_hash(&self, clip_id: &str, hash: &str) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE clips SET content_hash = ?1 WHERE id = ?2",
+            params![hash, clip_id],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // fold duplicate rows into `keep_id`: tags, collections, stars and descriptions carry
+    // over, the dropped rows go away and their paths are ignored by future scans.
+    // returns the merged description
+    pub fn merge_clips(&self, keep_id: &str, drop_ids: &[String]) -> Result<String, String> {
+        let conn = self.conn.lock();
+        let now = chrono::Utc::now().timestamp();
+        conn.execute_batch("BEGIN").map_err(|e| e.to_string())?;
+
+        let (mut description, mut starred): (String, bool) = conn.query_row(
+            "SELECT COALESCE(description, ''), starred FROM clips WHERE id = ?1",
+            params![keep_id],
+            |row| Ok((row.get(0)?, row.get::<_, i32>(1)? != 0)),
+        ).map_err(|e| { let _ = conn.execute_batch("ROLLBACK"); e.to_string() })?;
+
+        for drop_id in drop_ids.iter().filter(|id| id.as_str() != keep_id) {
+            let (path, desc, star): (String, String, bool) = match conn.query_row(
+                "SELECT path, COALESCE(description, ''), starred FROM clips WHERE id = ?1",
+                params![drop_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i32>(2)? != 0)),
+            ) {
+                Ok(r) => r,
+                Err(rusqlite::Error::QueryReturnedNoRows) => continue,
+                Err(e) => { let _ = conn.execute_batch("ROLLBACK"); return Err(e.to_string()); }
+            };
+
+            let desc = desc.trim();
+            if !desc.is_empty() && !description.contains(desc) {
+                if !description.trim().is_empty() { description.push_str("\n\n"); }
+                description.push_str(desc);
+            }
+            starred |= star;
+
+            let result = conn.execute(
+                "INSERT OR IGNORE INTO clip_tags (clip_id, tag_id)
+                 SELECT ?1, tag_id FROM clip_tags WHERE clip_id = ?2",
+                params![keep_id, drop_id],
+            ).and_then(|_| conn.execute(
+                "INSERT OR IGNORE INTO collection_clips (collection_id, clip_id, sort_order, added_at)
+                 SELECT collection_id, ?1, sort_order, added_at FROM collection_clips WHERE clip_id = ?2",
+                params![keep_id, drop_id],
+            )).and_then(|_| conn.execute(
+                "INSERT OR REPLACE INTO ignored_paths (path, reason, created_at) VALUES (?1, 'duplicate', ?2)",
+                params![path, now],
+            )).and_then(|_| conn.execute("DELETE FROM clips WHERE id = ?1", params![drop_id]));
+            if let Err(e) = result {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e.to_string());
+            }
+        }
+
+        conn.execute(
+            "UPDATE clips SET description = ?1, starred = ?2, updated_at = ?3 WHERE id = ?4",
+            params![description, starred as i32, now, keep_id],
+        ).map_err(|e| { let _ = conn.execute_batch("ROLLBACK"); e.to_string() })?;
+        conn.execute_batch("COMMIT").map_err(|e| e.to_string())?;
+        Ok(description)
+    }
+
+    pub fn get_ignored_paths(&self) -> Result<std::collections::HashSet<String>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT path FROM ignored_paths")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        Ok(rows.flatten().collect())
+    }
+
+    // None clears every entry
+    pub fn delete_ignored_paths(&self, paths: Option<&[String]>) -> Result<usize, String> {
+        let conn = self.conn.lock();
+        let Some(paths) = paths else {
+            return conn.execute("DELETE FROM ignored_paths", []).map_err(|e| e.to_string());
+        };
+        let mut removed = 0;
+        for path in paths {
+            removed += conn.execute("DELETE FROM ignored_paths WHERE path = ?1", params![path])
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(removed)
+    }
+
     // -- starred --
 
     pub fn toggle_star(&self, clip_id: &str, starred: bool) -> Result<(), String> {
@@ -485,6 +1106,18 @@ impl DbState {
         Ok(rows.filter_map(|r| r.ok()).collect())
     }
 
+    pub fn get_clip_collection_names(&self, clip_id: &str) -> Result<Vec<String>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT c.name FROM collections c
+             JOIN collection_clips cc ON cc.collection_id = c.id
+             WHERE cc.clip_id = ?1 ORDER BY c.sort_order, c.name"
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![clip_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
     // -- smart folders --
 
     pub fn get_all_smart_folders(&self) -> Result<Vec<SmartFolder>, String> {
@@ -530,6 +1163,70 @@ impl DbState {
         Ok(())
     }
 
+    // -- pattern profiles --
+
+    pub fn get_pattern_profiles(&self) -> Result<Vec<PatternProfile>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, name, regex, timestamp_format, builtin, created_at, updated_at
+             FROM pattern_profiles ORDER BY builtin DESC, name"
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PatternProfile {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                regex: row.get(2)?,
+                timestamp_format: row.get(3)?,
+                builtin: row.get::<_, i32>(4)? != 0,
+                created_at: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        }).map_err(|e| e.to_string())?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    pub fn create_pattern_profile(&self, id: &str, name: &str, regex: &str, timestamp_format: &str) -> Result<PatternProfile, String> {
+        let now = chrono::Utc::now().timestamp();
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO pattern_profiles (id, name, regex, timestamp_format, builtin, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, 0, ?5, ?5)",
+            params![id, name, regex, timestamp_format, now],
+        ).map_err(|e| e.to_string())?;
+        Ok(PatternProfile {
+            id: id.to_string(),
+            name: name.to_string(),
+            regex: regex.to_string(),
+            timestamp_format: timestamp_format.to_string(),
+            builtin: false,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn update_pattern_profile(&self, id: &str, name: &str, regex: &str, timestamp_format: &str) -> Result<(), String> {
+        let conn = self.conn.lock();
+        let changed = conn.execute(
+            "UPDATE pattern_profiles SET name = ?1, regex = ?2, timestamp_format = ?3, updated_at = ?4
+             WHERE id = ?5 AND builtin = 0",
+            params![name, regex, timestamp_format, chrono::Utc::now().timestamp(), id],
+        ).map_err(|e| e.to_string())?;
+        if changed == 0 {
+            return Err("profile not found or built-in".into());
+        }
+        Ok(())
+    }
+
+    pub fn delete_pattern_profile(&self, id: &str) -> Result<(), String> {
+        let conn = self.conn.lock();
+        let changed = conn.execute("DELETE FROM pattern_profiles WHERE id = ?1 AND builtin = 0", params![id])
+            .map_err(|e| e.to_string())?;
+        if changed == 0 {
+            return Err("profile not found or built-in".into());
+        }
+        Ok(())
+    }
+
     // -- waveforms --
 
     pub fn get_waveform(&self, clip_id: &str) -> Result<Option<Vec<u8>>, String> {
@@ -572,6 +1269,34 @@ impl DbState {
         Ok(())
     }
 
+    pub fn delete_meta(&self, key: &str) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM app_meta WHERE key = ?1", params![key]).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // -- perceptual hashes --
+
+    pub fn save_phash(&self, clip_id: &str, hashes: &[u8]) -> Result<(), String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO phashes (clip_id, hashes, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(clip_id) DO UPDATE SET hashes = ?2, updated_at = ?3",
+            params![clip_id, hashes, chrono::Utc::now().timestamp()],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn get_all_phashes(&self) -> Result<Vec<(String, Vec<u8>)>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT clip_id, hashes FROM phashes")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+        }).map_err(|e| e.to_string())?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
     // -- embeddings --
 
     pub fn upsert_embedding(&self, clip_id: &str, vector: &[u8]) -> Result<(), String> {
@@ -603,6 +1328,101 @@ impl DbState {
     }
 }
 
+// v6: move the app_meta watch dir list (+ per-dir profile/timezone maps) into
+// watch_dirs and link existing clips to the dir that contains them
+fn migrate_watch_dirs(conn: &Connection) -> Result<(), String> {
+    let meta = |key: &str| -> Result<Option<String>, String> {
+        conn.query_row("SELECT value FROM app_meta WHERE key = ?1", params![key], |row| row.get(0))
+            .optional().map_err(|e| e.to_string())
+    };
+    let paths: Vec<String> = meta("watch_dirs")?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    let profiles: std::collections::HashMap<String, String> = meta("watch_dir_profiles")?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    let zones: std::collections::HashMap<String, String> = meta("watch_dir_timezones")?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let now = chrono::Utc::now().timestamp();
+    for (i, path) in paths.iter().enumerate() {
+        let id = uuid::Uuid::new_v4().to_string();
+        let label = Path::new(path).file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone());
+        conn.execute(
+            "INSERT OR IGNORE INTO watch_dirs (id, path, label, color, max_depth, enabled, profile_id, timezone, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, 2, 1, (SELECT id FROM pattern_profiles WHERE id = ?5), ?6, ?7, ?7)",
+            params![id, path, label, WATCH_DIR_COLORS[i % WATCH_DIR_COLORS.len()], profiles.get(path), zones.get(path), now],
+        ).map_err(|e| e.to_string())?;
+    }
+
+    link_unowned_clips(conn)?;
+
+    conn.execute(
+        "DELETE FROM app_meta WHERE key IN ('watch_dir_profiles', 'watch_dir_timezones')",
+        [],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// filename timestamps are wall-clock times in the dir's zone. when the zone changes,
+// re-read the stored instants as the old wall-clock time in the new zone (like v5 did)
+fn rezone_filename_dates(conn: &Connection, dir_id: &str, from: Option<&str>, to: Option<&str>) -> Result<(), String> {
+    let zone = |name: Option<&str>| name.and_then(|n| crate::tz::parse_zone(n).ok());
+    let (from, to) = (zone(from), zone(to));
+    let rows: Vec<(String, i64)> = {
+        let mut stmt = conn.prepare(
+            "SELECT id, recorded_at FROM clips WHERE watch_dir_id = ?1 AND recorded_at_source = 'filename'"
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![dir_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    for (id, ts) in rows {
+        let Some(naive) = crate::tz::utc_to_local(ts, from) else { continue };
+        conn.execute(
+            "UPDATE clips SET recorded_at = ?1 WHERE id = ?2",
+            params![crate::tz::local_to_utc(naive, to), id],
+        ).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// attach clips that have no watch dir yet (rows from before watch dirs existed, or
+// whose dir was removed and re-added) to the deepest watch dir containing them
+fn link_unowned_clips(conn: &Connection) -> Result<usize, String> {
+    let dirs: Vec<(String, String, String)> = {
+        let mut stmt = conn.prepare("SELECT id, path, label FROM watch_dirs").map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    if dirs.is_empty() {
+        return Ok(0);
+    }
+    let clips: Vec<(String, String)> = {
+        let mut stmt = conn.prepare("SELECT id, path FROM clips WHERE watch_dir_id IS NULL").map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    let mut linked = 0;
+    for (clip_id, clip_path) in clips {
+        // deepest containing dir wins for nested watch dirs
+        let owner = dirs.iter()
+            .filter(|(_, dir, _)| Path::new(&clip_path).starts_with(dir))
+            .max_by_key(|(_, dir, _)| dir.len());
+        if let Some((dir_id, _, label)) = owner {
+            conn.execute(
+                "UPDATE clips SET watch_dir_id = ?1, dir_source = ?2 WHERE id = ?3",
+                params![dir_id, label, clip_id],
+            ).map_err(|e| e.to_string())?;
+            linked += 1;
+        }
+    }
+    Ok(linked)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -619,15 +1439,22 @@ mod tests {
             filename: format!("{}.mp4", id),
             path: path.to_string(),
             dir_source: "test".to_string(),
+            watch_dir_id: None,
             recorded_at: 1700000000,
+            recorded_at_source: "filename".to_string(),
             file_size: 1024,
+            file_mtime: Some(1700000000),
+            fingerprint: None,
             duration_secs: None,
             width: None,
             height: None,
             thumb_path: None,
+            thumb_detail_path: None,
+            cover_time: None,
             description: String::new(),
             tags: vec![],
             starred: false,
+            status: "online".to_string(),
             created_at: 1700000000,
             updated_at: 1700000000,
         }
@@ -639,7 +1466,7 @@ mod tests {
         // should be able to call init again without error (idempotent migration)
         db.init().unwrap();
         let version = db.get_meta("schema_version").unwrap();
-        assert_eq!(version, Some("2".to_string()));
+        assert_eq!(version, Some("14".to_string()));
     }
 
     #[test]
@@ -676,6 +1503,112 @@ mod tests {
         assert!(db.clip_exists_by_path("/test/clip1.mp4").unwrap());
     }
 
+    #[test]
+    fn test_relink_keeps_metadata() {
+        let db = setup();
+        let mut clip = make_clip("c1", "/test/old/clip1.mp4");
+        clip.fingerprint = Some("abc".to_string());
+        db.insert_clip(&clip).unwrap();
+        db.create_tag("t1", "funny", "#ff0000").unwrap();
+        db.add_clip_tag("c1", "t1").unwrap();
+        db.toggle_star("c1", true).unwrap();
+        db.update_description("c1", "clutch").unwrap();
+
+        db.relink_clip("c1", "/test/new/renamed.mp4", "renamed.mp4", None, "new").unwrap();
+
+        let clips = db.get_all_clips().unwrap();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].path, "/test/new/renamed.mp4");
+        assert_eq!(clips[0].filename, "renamed.mp4");
+        assert_eq!(clips[0].fingerprint.as_deref(), Some("abc"));
+        assert_eq!(clips[0].tags, vec!["t1"]);
+        assert!(clips[0].starred);
+        assert_eq!(clips[0].description, "clutch");
+        assert!(!db.clip_exists_by_path("/test/old/clip1.mp4").unwrap());
+    }
+
+    #[test]
+    fn test_status_and_purge() {
+        let db = setup();
+        db.insert_clip(&make_clip("c1", "/test/clip1.mp4")).unwrap();
+        db.insert_clip(&make_clip("c2", "/test/clip2.mp4")).unwrap();
+        db.insert_clip(&make_clip("c3", "/test/clip3.mp4")).unwrap();
+
+        db.set_clip_status(&["c1".to_string()], "offline").unwrap();
+        db.set_clip_status(&["c2".to_string()], "missing").unwrap();
+
+        // offline clips survive a missing-only purge
+        assert_eq!(db.purge_clips(&["missing"]).unwrap(), vec!["c2"]);
+        let clips = db.get_all_clips().unwrap();
+        assert_eq!(clips.len(), 2);
+        assert_eq!(clips.iter().find(|c| c.id == "c1").unwrap().status, "offline");
+
+        // relinking brings a clip back online
+        db.relink_clip("c1", "/other/clip1.mp4", "clip1.mp4", None, "other").unwrap();
+        let c1 = db.get_all_clips().unwrap().into_iter().find(|c| c.id == "c1").unwrap();
+        assert_eq!(c1.status, "online");
+    }
+
+    #[test]
+    fn test_invalidate_clip_content() {
+        let db = setup();
+        db.insert_clip(&make_clip("c1", "/test/clip1.mp4")).unwrap();
+        db.update_clip_meta("c1", 10.5, 1920, 1080).unwrap();
+        db.update_clip_thumb("c1", "/thumbs/c1.jpg", None, None).unwrap();
+        db.save_waveform("c1", &[0u8; 4], 1).unwrap();
+        db.upsert_embedding("c1", &[0u8; 4]).unwrap();
+        db.save_phash("c1", &[0u8; 8]).unwrap();
+        db.update_description("c1", "clutch").unwrap();
+
+        db.invalidate_clip_content("c1", 2048, 1700000500, Some("newfp")).unwrap();
+
+        let clip = &db.get_all_clips().unwrap()[0];
+        assert_eq!(clip.file_size, 2048);
+        assert_eq!(clip.file_mtime, Some(1700000500));
+        assert_eq!(clip.fingerprint.as_deref(), Some("newfp"));
+        assert!(clip.duration_secs.is_none());
+        assert!(clip.thumb_path.is_none());
+        // user metadata is kept
+        assert_eq!(clip.description, "clutch");
+        assert!(db.get_waveform("c1").unwrap().is_none());
+        assert!(db.get_all_embeddings().unwrap().is_empty());
+        assert!(db.get_all_phashes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_clips() {
+        let db = setup();
+        db.insert_clip(&make_clip("c1", "/test/clip1.mp4")).unwrap();
+        db.insert_clip(&make_clip("c2", "/backup/clip1.mp4")).unwrap();
+        db.insert_clip(&make_clip("c3", "/discord/clip1.mp4")).unwrap();
+
+        let tag = db.create_tag("t1", "clutch", "#ff0000").unwrap();
+        db.add_clip_tag("c2", &tag.id).unwrap();
+        let col = db.create_collection("col1", "Best of", "#6366f1").unwrap();
+        db.add_clips_to_collection(&col.id, &["c3".to_string()]).unwrap();
+        db.update_description("c1", "ace").unwrap();
+        db.update_description("c2", "ace").unwrap();
+        db.update_description("c3", "1v4 on B site").unwrap();
+        db.toggle_star("c3", true).unwrap();
+
+        let desc = db.merge_clips("c1", &["c2".to_string(), "c3".to_string()]).unwrap();
+        assert_eq!(desc, "ace\n\n1v4 on B site");
+
+        let clips = db.get_all_clips().unwrap();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].tags, vec!["t1"]);
+        assert!(clips[0].starred);
+        assert_eq!(db.get_collection_clip_ids("col1").unwrap(), vec!["c1"]);
+
+        let ignored = db.get_ignored_paths().unwrap();
+        assert!(ignored.contains("/backup/clip1.mp4") && ignored.contains("/discord/clip1.mp4"));
+
+        assert_eq!(db.delete_ignored_paths(Some(&["/backup/clip1.mp4".to_string()])).unwrap(), 1);
+        assert_eq!(db.get_ignored_paths().unwrap().len(), 1);
+        assert_eq!(db.delete_ignored_paths(None).unwrap(), 1);
+        assert!(db.get_ignored_paths().unwrap().is_empty());
+    }
+
     #[test]
     fn test_update_clip_meta() {
         let db = setup();
@@ -698,6 +1631,24 @@ mod tests {
         assert_eq!(clips[0].description, "cool clip");
     }
 
+    #[test]
+    fn test_set_and_shift_recorded_at() {
+        let db = setup();
+        db.insert_clip(&make_clip("c1", "/test/clip1.mp4")).unwrap();
+        db.insert_clip(&make_clip("c2", "/test/clip2.mp4")).unwrap();
+        assert_eq!(db.get_all_clips().unwrap()[0].recorded_at_source, "filename");
+
+        db.set_recorded_at(&["c1".to_string()], 1600000000).unwrap();
+        db.shift_recorded_at(&["c1".to_string(), "c2".to_string()], 3600).unwrap();
+
+        let clips = db.get_all_clips().unwrap();
+        let c1 = clips.iter().find(|c| c.id == "c1").unwrap();
+        let c2 = clips.iter().find(|c| c.id == "c2").unwrap();
+        assert_eq!(c1.recorded_at, 1600003600);
+        assert_eq!(c2.recorded_at, 1700003600);
+        assert!(clips.iter().all(|c| c.recorded_at_source == "manual"));
+    }
+
     #[test]
     fn test_starred() {
         let db = setup();
@@ -937,8 +1888,124 @@ mod tests {
         db.insert_clip(&make_clip("c1", "/test/clip1.mp4")).unwrap();
         assert!(db.get_all_clips().unwrap()[0].thumb_path.is_none());
 
-        db.update_clip_thumb("c1", "/thumbs/c1.jpg").unwrap();
+        db.update_clip_thumb("c1", "/thumbs/c1.jpg", Some("/thumbs/c1-detail.jpg"), Some("jpeg-q85-480-1280")).unwrap();
         let clips = db.get_all_clips().unwrap();
         assert_eq!(clips[0].thumb_path, Some("/thumbs/c1.jpg".to_string()));
+        assert_eq!(clips[0].thumb_detail_path, Some("/thumbs/c1-detail.jpg".to_string()));
+        assert!(db.get_stale_thumbs("jpeg-q85-480-1280").unwrap().is_empty());
+        assert_eq!(db.get_stale_thumbs("webp-q85-480-1280").unwrap().len(), 1);
+
+        db.set_clip_cover_time("c1", Some(12.5)).unwrap();
+        assert_eq!(db.get_clip("c1").unwrap().unwrap().cover_time, Some(12.5));
+        // a rewritten file drops the pick along with the rest of the derived data
+        db.invalidate_clip_content("c1", 2048, 1700000500, None).unwrap();
+        assert_eq!(db.get_clip("c1").unwrap().unwrap().cover_time, None);
+    }
+
+    #[test]
+    fn test_pattern_profiles() {
+        let db = setup();
+        let builtins = db.get_pattern_profiles().unwrap();
+        assert!(builtins.iter().any(|p| p.id == "obs" && p.builtin));
+        assert!(builtins.iter().any(|p| p.id == "all" && p.builtin));
+
+        let p = db.create_pattern_profile("p1", "Mine", r"^clip-(?P<ts>\d{8})$", "%Y%m%d").unwrap();
+        assert!(!p.builtin);
+        db.update_pattern_profile("p1", "Mine v2", r"^.*$", "").unwrap();
+        let mine = db.get_pattern_profiles().unwrap().into_iter().find(|p| p.id == "p1").unwrap();
+        assert_eq!(mine.name, "Mine v2");
+        assert_eq!(mine.timestamp_format, "");
+
+        // built-ins are read-only
+        assert!(db.update_pattern_profile("obs", "x", "^$", "").is_err());
+        assert!(db.delete_pattern_profile("obs").is_err());
+
+        db.delete_pattern_profile("p1").unwrap();
+        assert_eq!(db.get_pattern_profiles().unwrap().len(), builtins.len());
+    }
+
+    fn make_watch_dir(id: &str, path: &str) -> WatchDir {
+        WatchDir {
+            id: id.to_string(),
+            path: path.to_string(),
+            label: "Captures".to_string(),
+            color: "#6366f1".to_string(),
+            max_depth: Some(2),
+            include_globs: vec![],
+            exclude_globs: vec!["proxies/".to_string()],
+            enabled: true,
+            profile_id: Some("obs".to_string()),
+            timezone: None,
+            watch_mode: "auto".to_string(),
+            poll_interval_secs: None,
+            created_at: 1700000000,
+            updated_at: 1700000000,
+        }
+    }
+
+    #[test]
+    fn test_watch_dirs_crud() {
+        let db = setup();
+        db.create_watch_dir(&make_watch_dir("w1", "/a/Captures")).unwrap();
+        db.create_watch_dir(&make_watch_dir("w2", "/b/Captures")).unwrap();
+
+        let dirs = db.get_watch_dirs().unwrap();
+        assert_eq!(dirs.len(), 2);
+        assert_eq!(dirs[0].exclude_globs, vec!["proxies/"]);
+        assert_eq!(dirs[0].profile_id.as_deref(), Some("obs"));
+
+        // same path can't be added twice
+        assert!(db.create_watch_dir(&make_watch_dir("w3", "/a/Captures")).is_err());
+
+        let mut clip = make_clip("c1", "/b/Captures/clip1.mp4");
+        clip.watch_dir_id = Some("w2".to_string());
+        db.insert_clip(&clip).unwrap();
+
+        // relabel propagates to clips
+        let mut w2 = dirs[1].clone();
+        w2.label = "Desktop Captures".to_string();
+        w2.max_depth = None;
+        db.update_watch_dir(&w2).unwrap();
+        let clips = db.get_all_clips().unwrap();
+        assert_eq!(clips[0].dir_source, "Desktop Captures");
+        assert_eq!(clips[0].watch_dir_id.as_deref(), Some("w2"));
+        assert_eq!(db.get_watch_dirs().unwrap()[1].max_depth, None);
+
+        // a new timezone moves filename dates to the same wall-clock time in that zone
+        let before = db.get_all_clips().unwrap()[0].recorded_at;
+        let mut w2 = db.get_watch_dirs().unwrap()[1].clone();
+        w2.timezone = Some("UTC".to_string());
+        db.update_watch_dir(&w2).unwrap();
+        w2.timezone = Some("Etc/GMT-2".to_string());
+        db.update_watch_dir(&w2).unwrap();
+        let utc_wall = crate::tz::utc_to_local(before, None).unwrap().and_utc().timestamp();
+        assert_eq!(db.get_all_clips().unwrap()[0].recorded_at, utc_wall - 2 * 3600);
+
+        // removing the dir keeps its clips, marked missing
+        db.delete_watch_dir("w2").unwrap();
+        let clips = db.get_all_clips().unwrap();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].status, "missing");
+        assert_eq!(clips[0].watch_dir_id, None);
+        assert_eq!(db.get_watch_dirs().unwrap().len(), 1);
+
+        // the foreign key itself no longer cascades either
+        let mut clip = make_clip("c2", "/a/Captures/clip2.mp4");
+        clip.watch_dir_id = Some("w1".to_string());
+        db.insert_clip(&clip).unwrap();
+        db.conn.lock().execute("DELETE FROM watch_dirs WHERE id = 'w1'", []).unwrap();
+        assert_eq!(db.get_clip("c2").unwrap().unwrap().watch_dir_id, None);
+    }
+
+    #[test]
+    fn test_watch_dir_profile_cleared_on_profile_delete() {
+        let db = setup();
+        db.create_pattern_profile("p1", "Mine", r"^.*$", "").unwrap();
+        let mut dir = make_watch_dir("w1", "/a/Captures");
+        dir.profile_id = Some("p1".to_string());
+        db.create_watch_dir(&dir).unwrap();
+
+        db.delete_pattern_profile("p1").unwrap();
+        assert_eq!(db.get_watch_dirs().unwrap()[0].profile_id, None);
     }
 }
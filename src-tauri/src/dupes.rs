@@ -0,0 +1,150 @@
+use crate::db::{Clip, DbState};
+use crate::fingerprint;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub content_hash: String,
+    pub file_size: i64,
+    // best copy to keep first: starred, most tags, then oldest in the library
+    pub clip_ids: Vec<String>,
+}
+
+// byte-identical clips across all watch dirs. sizes are compared first so the full
+// hash only runs on files that could possibly match; hashes are cached per clip
+pub fn find_duplicates(db: &DbState) -> Result<Vec<DuplicateGroup>, String> {
+    let clips: Vec<Clip> = db.get_all_clips()?
+        .into_iter()
+        .filter(|c| c.status == "online" && c.file_size > 0)
+        .collect();
+    let mut hashes = db.get_content_hashes()?;
+
+    let mut by_hash: HashMap<(i64, String), Vec<&Clip>> = HashMap::new();
+    for same_size in group_by(&clips, |c| c.file_size).into_values().filter(|g| g.len() > 1) {
+        for clip in same_size {
+            let hash = match clip_hash(db, clip, hashes.remove(&clip.id)) {
+                Ok(h) => h,
+                Err(e) => { eprintln!("hash {}: {}", clip.path, e); continue; }
+            };
+            by_hash.entry((clip.file_size, hash)).or_default().push(clip);
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = by_hash.into_iter()
+        .filter(|(_, g)| g.len() > 1)
+        .map(|((file_size, content_hash), mut g)| {
+            g.sort_by_key(|c| keep_rank(c));
+            DuplicateGroup { content_hash, file_size, clip_ids: g.iter().map(|c| c.id.clone()).collect() }
+        })
+        .collect();
+    // biggest wasted space first
+    groups.sort_by_key(|g| std::cmp::Reverse(g.file_size * (g.clip_ids.len() as i64 - 1)));
+    Ok(groups)
+}
+
+// the clips `resolve_duplicates` is about to drop, after checking each really is a
+// byte-identical copy of `keep_id`. the ids come from the frontend, so don't trust them
+pub fn verify_duplicates(db: &DbState, keep_id: &str, drop_ids: &[String]) -> Result<Vec<Clip>, String> {
+    let keep = db.get_clip(keep_id)?.ok_or_else(|| format!("clip not found: {}", keep_id))?;
+    let mut hashes = db.get_content_hashes()?;
+    let keep_hash = clip_hash(db, &keep, hashes.remove(&keep.id))?;
+
+    let mut drops = vec![];
+    for id in drop_ids.iter().filter(|id| id.as_str() != keep_id) {
+        let clip = db.get_clip(id)?.ok_or_else(|| format!("clip not found: {}", id))?;
+        if clip.file_size != keep.file_size || clip_hash(db, &clip, hashes.remove(&clip.id))? != keep_hash {
+            return Err(format!("{} is not identical to {}", clip.path, keep.path));
+        }
+        drops.push(clip);
+    }
+    Ok(drops)
+}
+
+// cached content hash, computed (and stored) on first use
+fn clip_hash(db: &DbState, clip: &Clip, cached: Option<String>) -> Result<String, String> {
+    if let Some(hash) = cached {
+        return Ok(hash);
+    }
+    let hash = fingerprint::content_hash(Path::new(&clip.path))?;
+    let _ = db.update_clip_content_hash(&clip.id, &hash);
+    Ok(hash)
+}
+
+fn group_by<K: std::hash::Hash + Eq>(clips: &[Clip], key: impl Fn(&Clip) -> K) -> HashMap<K, Vec<&Clip>> {
+    let mut map: HashMap<K, Vec<&Clip>> = HashMap::new();
+    for clip in clips {
+        map.entry(key(clip)).or_default().push(clip);
+    }
+    map
+}
+
+fn keep_rank(clip: &Clip) -> (bool, std::cmp::Reverse<usize>, i64) {
+    (!clip.starred, std::cmp::Reverse(clip.tags.len()), clip.created_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(id: &str, starred: bool, tags: usize, created_at: i64) -> Clip {
+        Clip {
+            id: id.to_string(),
+            filename: format!("{}.mp4", id),
+            path: format!("/clips/{}.mp4", id),
+            dir_source: "clips".to_string(),
+            watch_dir_id: None,
+            recorded_at: 0,
+            recorded_at_source: "filename".to_string(),
+            file_size: 1024,
+            file_mtime: None,
+            fingerprint: None,
+            duration_secs: None,
+            width: None,
+            height: None,
+            thumb_path: None,
+            thumb_detail_path: None,
+            cover_time: None,
+            description: String::new(),
+            tags: (0..tags).map(|i| format!("t{}", i)).collect(),
+            starred,
+            status: "online".to_string(),
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_keep_rank() {
+        let mut clips = [clip("plain", false, 0, 1), clip("tagged", false, 2, 5), clip("starred", true, 0, 9)];
+        clips.sort_by_key(keep_rank);
+        let order: Vec<&str> = clips.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(order, ["starred", "tagged", "plain"]);
+    }
+
+    #[test]
+    fn test_verify_duplicates() {
+        let root = std::env::temp_dir().join(format!("boxy-dupes-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let db = DbState::in_memory().unwrap();
+        db.init().unwrap();
+        for (id, bytes) in [("a", "same bytes"), ("b", "same bytes"), ("c", "other byte")] {
+            let path = root.join(format!("{}.mp4", id));
+            std::fs::write(&path, bytes).unwrap();
+            let mut c = clip(id, false, 0, 1);
+            c.path = path.to_string_lossy().to_string();
+            c.file_size = bytes.len() as i64;
+            db.insert_clip(&c).unwrap();
+        }
+
+        let drops = verify_duplicates(&db, "a", &["a".to_string(), "b".to_string()]).unwrap();
+        assert_eq!(drops.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), ["b"]);
+        // same size, different content
+        assert!(verify_duplicates(&db, "a", &["b".to_string(), "c".to_string()]).is_err());
+        assert!(verify_duplicates(&db, "a", &["nope".to_string()]).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,6 +1,9 @@
 use crate::ffmpeg;
+use crate::metadata::{self, ClipMetadata};
+use std::path::Path;
 
 /// Trim/cut a clip. Uses stream copy by default (fast), re-encode when `precise` is true.
+/// `meta` is written into the output's container tags.
 pub fn trim_clip(
     ffmpeg_path: &str,
     input: &str,
@@ -8,6 +11,7 @@ pub fn trim_clip(
     start: f64,
     end: f64,
     precise: bool,
+    meta: Option<&ClipMetadata>,
 ) -> Result<(), String> {
     let duration = end - start;
     let start_s = format!("{:.3}", start);
@@ -21,6 +25,8 @@ pub fn trim_clip(
         args.extend_from_slice(&["-c", "copy", "-avoid_negative_ts", "make_zero"]);
     }
 
+    let meta_args = output_metadata(meta, output);
+    args.extend(meta_args.iter().map(String::as_str));
     args.push(output);
 
     let out = ffmpeg::cmd(ffmpeg_path)
@@ -36,10 +42,12 @@ pub fn trim_clip(
 }
 
 /// Merge clips using concat demuxer (stream copy). Falls back to re-encode on failure.
+/// `meta` is written into the output's container tags.
 pub fn merge_clips(
     ffmpeg_path: &str,
     inputs: &[String],
     output: &str,
+    meta: Option<&ClipMetadata>,
 ) -> Result<(), String> {
     // write concat list to temp file
     let tmp = std::env::temp_dir().join("boxy_concat.txt");
@@ -50,10 +58,13 @@ pub fn merge_clips(
         .join("\n");
     std::fs::write(&tmp, &content).map_err(|e| format!("write concat list: {}", e))?;
     let tmp_str = tmp.to_string_lossy().to_string();
+    let meta_args = output_metadata(meta, output);
 
     // try stream copy first
     let out = ffmpeg::cmd(ffmpeg_path)
-        .args(["-y", "-f", "concat", "-safe", "0", "-i", &tmp_str, "-c", "copy", output])
+        .args(["-y", "-f", "concat", "-safe", "0", "-i", &tmp_str, "-c", "copy"])
+        .args(&meta_args)
+        .arg(output)
         .output()
         .map_err(|e| format!("ffmpeg merge: {}", e))?;
 
@@ -61,7 +72,9 @@ pub fn merge_clips(
         // fallback: re-encode
         let out2 = ffmpeg::cmd(ffmpeg_path)
             .args(["-y", "-f", "concat", "-safe", "0", "-i", &tmp_str,
-                   "-c:v", "libx264", "-preset", "fast", "-crf", "22", "-c:a", "aac", output])
+                   "-c:v", "libx264", "-preset", "fast", "-crf", "22", "-c:a", "aac"])
+            .args(&meta_args)
+            .arg(output)
             .output()
             .map_err(|e| format!("ffmpeg merge re-encode: {}", e))?;
 
@@ -144,13 +157,14 @@ pub fn capture_frame(
     Ok(())
 }
 
-/// Compress clip with quality presets.
+/// Compress clip with quality presets. `meta` is written into the output's container tags.
 pub fn compress_clip(
     ffmpeg_path: &str,
     input: &str,
     output: &str,
     quality: &str,
     max_width: Option<u32>,
+    meta: Option<&ClipMetadata>,
 ) -> Result<(), String> {
     let (crf, preset) = match quality {
         "high" => ("22", "medium"),
@@ -172,6 +186,8 @@ pub fn compress_clip(
     if !vf.is_empty() {
         args.extend_from_slice(&["-vf", &vf]);
     }
+    let meta_args = output_metadata(meta, output);
+    args.extend(meta_args.iter().map(String::as_str));
     args.push(output);
 
     let out = ffmpeg::cmd(ffmpeg_path)
@@ -239,3 +255,44 @@ pub fn generate_waveform(
 
     Ok(bars)
 }
+
+/// Remux into a webview-friendly mp4. Stream copy first, re-encode if the codecs don't fit;
+/// `reencode` skips the copy for files whose streams copied fine but still won't decode.
+/// Written to a `.part` file and renamed, so a killed run never leaves a broken cache.
+pub fn remux_for_playback(
+    ffmpeg_path: &str,
+    input: &str,
+    output: &str,
+    reencode: bool,
+) -> Result<(), String> {
+    let part = format!("{}.part", output);
+    let run = |codec: &[&str]| {
+        ffmpeg::cmd(ffmpeg_path)
+            .args(["-y", "-i", input, "-map", "0:v:0", "-map", "0:a:0?"])
+            .args(codec)
+            .args(["-movflags", "+faststart", "-f", "mp4", &part])
+            .output()
+    };
+
+    let copied = !reencode && run(&["-c", "copy"])
+        .map_err(|e| format!("ffmpeg remux: {}", e))?
+        .status.success();
+
+    if !copied {
+        // re-encode (e.g. vp8/wmv in a container mp4 can't hold)
+        let out = run(&["-c:v", "libx264", "-preset", "veryfast", "-crf", "20", "-c:a", "aac"])
+            .map_err(|e| format!("ffmpeg remux re-encode: {}", e))?;
+
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            let _ = std::fs::remove_file(&part);
+            return Err(format!("remux failed: {}", stderr.chars().take(500).collect::<String>()));
+        }
+    }
+    std::fs::rename(&part, output).map_err(|e| format!("rename: {}", e))
+}
+
+/// `-metadata` args for an edit output, empty when nothing is inherited.
+fn output_metadata(meta: Option<&ClipMetadata>, output: &str) -> Vec<String> {
+    meta.map(|m| metadata::args(m, Path::new(output))).unwrap_or_default()
+}
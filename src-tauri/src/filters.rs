@@ -0,0 +1,145 @@
+use crate::db::WatchDir;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+
+// depth + include/exclude rules for one watch dir, shared by the scanner and the watcher.
+// excludes use gitignore syntax (`proxies/`, `/exports`, `*.tmp.mp4`, `!keep/`),
+// includes are plain globs matched against the path relative to the watch root
+pub struct DirFilter {
+    root: PathBuf,
+    max_depth: Option<u32>,
+    exclude: Gitignore,
+    include: Option<GlobSet>,
+}
+
+impl DirFilter {
+    pub fn new(dir: &WatchDir) -> Result<Self, String> {
+        if dir.max_depth == Some(0) {
+            return Err("max depth must be at least 1".into());
+        }
+
+        let root = PathBuf::from(&dir.path);
+        let mut builder = GitignoreBuilder::new(&root);
+        for line in &dir.exclude_globs {
+            builder.add_line(None, line).map_err(|e| format!("exclude {}: {}", line, e))?;
+        }
+        let exclude = builder.build().map_err(|e| e.to_string())?;
+
+        let include = if dir.include_globs.is_empty() {
+            None
+        } else {
+            let mut set = GlobSetBuilder::new();
+            for pattern in &dir.include_globs {
+                set.add(Glob::new(pattern).map_err(|e| format!("include {}: {}", pattern, e))?);
+            }
+            Some(set.build().map_err(|e| e.to_string())?)
+        };
+
+        Ok(Self { root, max_depth: dir.max_depth, exclude, include })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // whether a watcher on the root needs to see subfolders at all
+    pub fn recursive(&self) -> bool {
+        self.max_depth != Some(1)
+    }
+
+    // should the walk descend into this directory
+    pub fn allows_dir(&self, path: &Path) -> bool {
+        match self.depth(path) {
+            Some(0) => true,
+            // files inside sit one level deeper than the dir itself
+            Some(d) => self.max_depth.is_none_or(|max| d < max as usize) && !self.excluded(path, true),
+            None => false,
+        }
+    }
+
+    pub fn allows_file(&self, path: &Path) -> bool {
+        let depth = match self.depth(path) {
+            Some(d) if d > 0 => d,
+            _ => return false,
+        };
+        if self.max_depth.is_some_and(|max| depth > max as usize) { return false; }
+        if self.excluded(path, false) { return false; }
+
+        match &self.include {
+            Some(set) => path.strip_prefix(&self.root).is_ok_and(|rel| set.is_match(rel)),
+            None => true,
+        }
+    }
+
+    fn excluded(&self, path: &Path, is_dir: bool) -> bool {
+        self.exclude.matched_path_or_any_parents(path, is_dir).is_ignore()
+    }
+
+    // 0 for the root itself, 1 for its direct children, None when outside the root
+    fn depth(&self, path: &Path) -> Option<usize> {
+        path.strip_prefix(&self.root).ok().map(|rel| rel.components().count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(max_depth: Option<u32>, include: &[&str], exclude: &[&str]) -> WatchDir {
+        WatchDir {
+            id: "w1".to_string(),
+            path: "/clips".to_string(),
+            label: "clips".to_string(),
+            color: "#6366f1".to_string(),
+            max_depth,
+            include_globs: include.iter().map(|s| s.to_string()).collect(),
+            exclude_globs: exclude.iter().map(|s| s.to_string()).collect(),
+            enabled: true,
+            profile_id: None,
+            timezone: None,
+            watch_mode: "auto".to_string(),
+            poll_interval_secs: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_depth() {
+        let f = DirFilter::new(&dir(Some(2), &[], &[])).unwrap();
+        assert!(f.allows_file(Path::new("/clips/a.mp4")));
+        assert!(f.allows_file(Path::new("/clips/Game/a.mp4")));
+        assert!(!f.allows_file(Path::new("/clips/Game/2024-01-28/a.mp4")));
+        assert!(f.allows_dir(Path::new("/clips/Game")));
+        assert!(!f.allows_dir(Path::new("/clips/Game/2024-01-28")));
+        assert!(!f.allows_file(Path::new("/elsewhere/a.mp4")));
+
+        let unlimited = DirFilter::new(&dir(None, &[], &[])).unwrap();
+        assert!(unlimited.allows_file(Path::new("/clips/a/b/c/d/a.mp4")));
+        assert!(unlimited.recursive());
+        assert!(!DirFilter::new(&dir(Some(1), &[], &[])).unwrap().recursive());
+        assert!(DirFilter::new(&dir(Some(0), &[], &[])).is_err());
+    }
+
+    #[test]
+    fn test_excludes() {
+        let f = DirFilter::new(&dir(None, &[], &[".trash/", "proxies/", "/exports", "*.part.mp4", "!keep.part.mp4"])).unwrap();
+        assert!(!f.allows_dir(Path::new("/clips/.trash")));
+        assert!(!f.allows_file(Path::new("/clips/Game/proxies/a.mp4")));
+        assert!(!f.allows_file(Path::new("/clips/exports/a.mp4")));
+        // anchored pattern only applies at the root
+        assert!(f.allows_file(Path::new("/clips/Game/exports/a.mp4")));
+        assert!(!f.allows_file(Path::new("/clips/a.part.mp4")));
+        assert!(f.allows_file(Path::new("/clips/keep.part.mp4")));
+        assert!(f.allows_file(Path::new("/clips/Game/a.mp4")));
+    }
+
+    #[test]
+    fn test_includes() {
+        let f = DirFilter::new(&dir(None, &["Valorant/**", "*.mkv"], &[])).unwrap();
+        assert!(f.allows_file(Path::new("/clips/Valorant/a.mp4")));
+        assert!(f.allows_file(Path::new("/clips/a.mkv")));
+        assert!(!f.allows_file(Path::new("/clips/Apex/a.mp4")));
+    }
+}
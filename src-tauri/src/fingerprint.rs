@@ -0,0 +1,39 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const CHUNK: u64 = 64 * 1024;
+
+// cheap content identity: size + blake3 of the first and last 64KiB.
+// survives renames/moves, changes whenever the file is re-encoded or rewritten
+pub fn fingerprint(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("open {}: {}", path.display(), e))?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&size.to_le_bytes());
+
+    let mut buf = vec![0u8; CHUNK.min(size) as usize];
+    file.read_exact(&mut buf).map_err(|e| e.to_string())?;
+    hasher.update(&buf);
+
+    // tail, without re-reading bytes the head already covered
+    let tail_start = size.saturating_sub(CHUNK).max(CHUNK.min(size));
+    if tail_start < size {
+        let mut tail = vec![0u8; (size - tail_start) as usize];
+        file.seek(SeekFrom::Start(tail_start)).map_err(|e| e.to_string())?;
+        file.read_exact(&mut tail).map_err(|e| e.to_string())?;
+        hasher.update(&tail);
+    }
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+// full blake3 of the file, for exact duplicate checks. reads everything, so only
+// run it on clips that already share a size with something else
+pub fn content_hash(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("open {}: {}", path.display(), e))?;
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
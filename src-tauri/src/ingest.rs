@@ -0,0 +1,396 @@
+use crate::db::{Clip, DbState, WatchDir};
+use crate::filters::DirFilter;
+use crate::fingerprint;
+use crate::media;
+use crate::patterns::Matcher;
+use crate::scan::{self, ScanProgress, SkipReason, SkippedFile};
+use crate::sidecar;
+use crate::tz;
+use chrono::Datelike;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+pub const DEFAULT_TEMPLATE: &str = "{year}/{month}/{source}/{filename}";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestOptions {
+    pub source: String,
+    // watch dir that acts as the managed library root
+    pub library_id: String,
+    pub template: Option<String>,
+    // fills {source}, defaults to the source folder name
+    pub label: Option<String>,
+    // move instead of copy
+    #[serde(default)]
+    pub move_files: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestReport {
+    // destination paths of the registered clips
+    pub imported: Vec<String>,
+    pub skipped: Vec<SkippedFile>,
+    pub failed: Vec<IngestFailure>,
+}
+
+// fields a template can use. `source` is the ingest label, usually the source folder name
+struct TemplateVars<'a> {
+    recorded_at: i64,
+    // the library folder's zone, so dates match the filename times (system zone when None)
+    zone: Option<Tz>,
+    source: &'a str,
+    filename: &'a str,
+}
+
+// render `template` into a path relative to the library root.
+// placeholders: {year} {month} {day} {source} {filename} {stem} {ext}
+fn render_template(template: &str, vars: &TemplateVars) -> Result<PathBuf, String> {
+    let local = tz::utc_to_local(vars.recorded_at, vars.zone).ok_or("recorded_at out of range")?;
+    let path = Path::new(vars.filename);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(vars.filename);
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or_default();
+
+    let rendered = template
+        .replace("{year}", &format!("{:04}", local.year()))
+        .replace("{month}", &format!("{:02}", local.month()))
+        .replace("{day}", &format!("{:02}", local.day()))
+        .replace("{source}", &sanitize(vars.source))
+        .replace("{filename}", &sanitize(vars.filename))
+        .replace("{stem}", &sanitize(stem))
+        .replace("{ext}", &sanitize(ext));
+
+    if rendered.contains('{') {
+        return Err(format!("unknown placeholder in template: {}", template));
+    }
+
+    let mut out = PathBuf::new();
+    for part in rendered.split(['/', '\\']).filter(|p| !p.is_empty()) {
+        // no escaping the library root
+        match Path::new(part).components().next() {
+            Some(Component::Normal(_)) => out.push(part),
+            _ => return Err(format!("invalid path segment in template: {}", part)),
+        }
+    }
+    if out.as_os_str().is_empty() {
+        return Err("template renders to an empty path".into());
+    }
+    Ok(out)
+}
+
+// keep path segments valid on every OS the library might be opened from
+fn sanitize(s: &str) -> String {
+    let cleaned: String = s.chars()
+        .map(|c| if matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') || c.is_control() { '_' } else { c })
+        .collect();
+    let trimmed = cleaned.trim().trim_end_matches('.');
+    if trimmed.is_empty() { "_".to_string() } else { trimmed.to_string() }
+}
+
+// `clip.mp4` -> `clip (1).mp4` until the name is free
+fn free_path(path: PathBuf) -> PathBuf {
+    if !path.exists() { return path; }
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = path.extension().map(|s| format!(".{}", s.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, ext)))
+        .find(|p| !p.exists())
+        .unwrap()
+}
+
+// copy, then hash both sides before the file gets its real name
+fn copy_verified(src: &Path, dest: &Path, src_hash: &str) -> Result<(), String> {
+    let part = dest.with_extension(format!(
+        "{}.part",
+        dest.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default()
+    ));
+    std::fs::copy(src, &part).map_err(|e| format!("copy: {}", e))?;
+    match fingerprint::content_hash(&part) {
+        Ok(h) if h == src_hash => {}
+        Ok(_) => {
+            let _ = std::fs::remove_file(&part);
+            return Err("hash mismatch after copy".into());
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&part);
+            return Err(e);
+        }
+    }
+    std::fs::rename(&part, dest).map_err(|e| format!("rename: {}", e))
+}
+
+// move when asked, falling back to a verified copy + delete across volumes
+fn transfer(src: &Path, dest: &Path, src_hash: &str, move_files: bool) -> Result<(), String> {
+    if move_files && std::fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+    copy_verified(src, dest, src_hash)?;
+    if move_files {
+        std::fs::remove_file(src).map_err(|e| format!("remove source: {}", e))?;
+    }
+    Ok(())
+}
+
+// copy (or move) every clip under the source into the library watch dir following
+// the template, and register them. files already in the library, by content, are skipped
+pub fn ingest(db: &DbState, ffprobe_path: &str, opts: &IngestOptions, on_progress: &dyn Fn(&ScanProgress)) -> Result<IngestReport, String> {
+    let library: WatchDir = db.get_watch_dirs()?
+        .into_iter()
+        .find(|d| d.id == opts.library_id)
+        .ok_or("library folder not found")?;
+    let source = Path::new(&opts.source);
+    let template = opts.template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+    let move_files = opts.move_files;
+
+    if !source.is_dir() {
+        return Err(format!("not a directory: {}", source.display()));
+    }
+    let root = Path::new(&library.path);
+    if !root.is_dir() {
+        return Err(format!("library folder is offline: {}", library.path));
+    }
+    if source.starts_with(root) || root.starts_with(source) {
+        return Err("source and library folders overlap".into());
+    }
+
+    let source_label = opts.label.clone().filter(|l| !l.trim().is_empty()).unwrap_or_else(|| {
+        source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "import".into())
+    });
+
+    // filename times are wall-clock in the library folder's zone, same as a scan would read them
+    let zone = library.timezone.as_deref().and_then(|name| tz::parse_zone(name).ok());
+
+    // make sure the scanner will still see what we put there
+    let filter = DirFilter::new(&library)?;
+    let probe = render_template(template, &TemplateVars { recorded_at: 0, zone, source: &source_label, filename: "x.mp4" })?;
+    if !filter.allows_file(&root.join(&probe)) {
+        return Err("template puts clips where the library folder's depth or exclude rules won't scan them".into());
+    }
+
+    // any profile that can read a timestamp out of the name, SD cards mix cameras
+    let matchers: Vec<Matcher> = db.get_pattern_profiles()?
+        .iter()
+        .filter_map(|p| Matcher::new(p).ok())
+        .collect();
+    let default_matcher = matchers.iter().find(|m| m.has_timestamp())
+        .ok_or("no pattern profiles")?;
+
+    let mut known: HashSet<String> = db.get_all_clips()?
+        .into_iter()
+        .filter_map(|c| c.fingerprint)
+        .collect();
+
+    let files: Vec<PathBuf> = WalkDir::new(source).min_depth(1).sort_by_file_name().into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file() && media::is_video(e.path()))
+        .map(|e| e.into_path())
+        .collect();
+    let total = files.len();
+    let mut report = IngestReport::default();
+    let now = chrono::Utc::now().timestamp();
+
+    for (done, src) in files.iter().enumerate() {
+        on_progress(&ScanProgress {
+            phase: "ingesting",
+            total,
+            done,
+            dir: source.to_string_lossy().to_string(),
+            file: Some(src.to_string_lossy().to_string()),
+            files_seen: done,
+        });
+
+        let src_str = src.to_string_lossy().to_string();
+        let skip = |reason| SkippedFile { path: src_str.clone(), reason };
+
+        let (Some(filename), Ok(meta)) = (src.file_name().and_then(|n| n.to_str()), std::fs::metadata(src)) else {
+            report.skipped.push(skip(SkipReason::Unreadable));
+            continue;
+        };
+        let Ok(fp) = fingerprint::fingerprint(src) else {
+            report.skipped.push(skip(SkipReason::Unreadable));
+            continue;
+        };
+        // in the library already, or a second copy within this dump
+        if !known.insert(fp.clone()) {
+            report.skipped.push(skip(SkipReason::Duplicate));
+            continue;
+        }
+
+        let stem = src.file_stem().and_then(|n| n.to_str()).unwrap_or_default();
+        let matcher = matchers.iter().find(|m| m.timestamp(stem).is_some()).unwrap_or(default_matcher);
+        let Some((recorded_at, recorded_at_source)) =
+            scan::resolve_recorded_at(matcher, stem, src, Some(&meta), zone, Some(ffprobe_path))
+        else {
+            report.skipped.push(skip(SkipReason::Unreadable));
+            continue;
+        };
+
+        let result = (|| -> Result<(PathBuf, String), String> {
+            let rel = render_template(template, &TemplateVars { recorded_at, zone, source: &source_label, filename })?;
+            let dest = free_path(root.join(rel));
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let hash = fingerprint::content_hash(src)?;
+            transfer(src, &dest, &hash, move_files)?;
+            // sidecars follow the clip under its new name
+            for (from, to) in [
+                (sidecar::json_path(src), sidecar::json_path(&dest)),
+                (sidecar::xmp_path(src), sidecar::xmp_path(&dest)),
+            ] {
+                if from.exists() {
+                    let moved = move_files && std::fs::rename(&from, &to).is_ok();
+                    if !moved {
+                        std::fs::copy(&from, &to).map_err(|e| format!("sidecar: {}", e))?;
+                    }
+                }
+            }
+            Ok((dest, hash))
+        })();
+        let (dest, hash) = match result {
+            Ok(r) => r,
+            Err(error) => {
+                known.remove(&fp);
+                report.failed.push(IngestFailure { path: src_str.clone(), error });
+                continue;
+            }
+        };
+
+        let dest_str = dest.to_string_lossy().to_string();
+        let clip = Clip {
+            id: uuid::Uuid::new_v4().to_string(),
+            filename: dest.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            path: dest_str.clone(),
+            dir_source: library.label.clone(),
+            watch_dir_id: Some(library.id.clone()),
+            recorded_at,
+            recorded_at_source: recorded_at_source.to_string(),
+            file_size: meta.len() as i64,
+            file_mtime: std::fs::metadata(&dest).ok().as_ref().and_then(scan::mtime_secs),
+            fingerprint: Some(fp),
+            duration_secs: None,
+            width: None,
+            height: None,
+            thumb_path: None,
+            thumb_detail_path: None,
+            cover_time: None,
+            description: String::new(),
+            tags: vec![],
+            starred: false,
+            status: "online".to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        db.insert_clip(&clip)?;
+        let _ = db.update_clip_content_hash(&clip.id, &hash);
+        if let Some(sc) = sidecar::read(&dest, &sidecar::mode(db)) {
+            if let Err(e) = sidecar::apply(db, &clip, &sc) {
+                eprintln!("sidecar {}: {}", dest_str, e);
+            }
+        }
+        report.imported.push(dest_str);
+    }
+
+    on_progress(&ScanProgress {
+        phase: "complete", total, done: total, dir: String::new(), file: None, files_seen: total,
+    });
+    eprintln!(
+        "ingest: {} imported, {} skipped, {} failed",
+        report.imported.len(), report.skipped.len(), report.failed.len(),
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let naive = |s: &str| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let ts = tz::local_to_utc(naive("2024-01-28 18:40:28"), None);
+        let vars = TemplateVars { recorded_at: ts, zone: None, source: "SD: card", filename: "clip.mp4" };
+        assert_eq!(
+            render_template(DEFAULT_TEMPLATE, &vars).unwrap(),
+            ["2024", "01", "SD_ card", "clip.mp4"].iter().collect::<PathBuf>()
+        );
+        assert_eq!(
+            render_template("{year}-{month}-{day}/{stem}.{ext}", &vars).unwrap(),
+            ["2024-01-28", "clip.mp4"].iter().collect::<PathBuf>()
+        );
+        assert!(render_template("../{filename}", &vars).is_err());
+        assert!(render_template("{game}/{filename}", &vars).is_err());
+
+        // 23:30 on the last of the month in the library's zone stays in that month's
+        // folder whatever the machine's zone is; a zone further east is already in February
+        let tokyo = tz::parse_zone("Asia/Tokyo").ok();
+        let ts = tz::local_to_utc(naive("2024-01-31 23:30:00"), tokyo);
+        let vars = TemplateVars { recorded_at: ts, zone: tokyo, ..vars };
+        assert_eq!(render_template("{year}/{month}/{day}", &vars).unwrap(), ["2024", "01", "31"].iter().collect::<PathBuf>());
+        let vars = TemplateVars { zone: tz::parse_zone("Pacific/Kiritimati").ok(), ..vars };
+        assert_eq!(render_template("{year}/{month}/{day}", &vars).unwrap(), ["2024", "02", "01"].iter().collect::<PathBuf>());
+    }
+
+    #[test]
+    fn test_ingest() {
+        let base = std::env::temp_dir().join(format!("boxy-ingest-{}", uuid::Uuid::new_v4()));
+        let card = base.join("card");
+        let library = base.join("library");
+        std::fs::create_dir_all(card.join("DCIM")).unwrap();
+        std::fs::create_dir_all(&library).unwrap();
+        std::fs::write(card.join("DCIM/2024-01-28 18-40-28.mp4"), b"clip one").unwrap();
+        std::fs::write(card.join("DCIM/copy.mp4"), b"clip one").unwrap();
+        std::fs::write(card.join("notes.txt"), b"not media").unwrap();
+
+        let db = DbState::in_memory().unwrap();
+        db.init().unwrap();
+        let mut dir = scan::add_watch_dir(&db, &library.to_string_lossy(), None, None).unwrap();
+        let opts = IngestOptions {
+            source: card.to_string_lossy().to_string(),
+            library_id: dir.id.clone(),
+            template: None,
+            label: None,
+            move_files: false,
+        };
+
+        // default depth 2 can't hold year/month/source/file
+        assert!(ingest(&db, "ffprobe-not-installed", &opts, &|_| {}).is_err());
+        dir.max_depth = None;
+        dir.timezone = Some("Asia/Tokyo".into());
+        db.update_watch_dir(&dir).unwrap();
+
+        let report = ingest(&db, "ffprobe-not-installed", &opts, &|_| {}).unwrap();
+        assert_eq!(report.imported.len(), 1);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(matches!(report.skipped[0].reason, SkipReason::Duplicate));
+        let expected: PathBuf = [library.as_path(), Path::new("2024/01/card/2024-01-28 18-40-28.mp4")].iter().collect();
+        assert_eq!(Path::new(&report.imported[0]), expected);
+        assert_eq!(std::fs::read(&expected).unwrap(), b"clip one");
+        // copy mode leaves the card alone
+        assert!(card.join("DCIM/copy.mp4").exists());
+
+        let clips = db.get_all_clips().unwrap();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].watch_dir_id.as_deref(), Some(dir.id.as_str()));
+        // 18:40:28 on a camera in the library's zone (UTC+9)
+        assert_eq!(clips[0].recorded_at, 1706467228 - 9 * 3600);
+
+        // second run: everything is already in the library
+        let report = ingest(&db, "ffprobe-not-installed", &opts, &|_| {}).unwrap();
+        assert!(report.imported.is_empty());
+        assert_eq!(report.skipped.len(), 2);
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
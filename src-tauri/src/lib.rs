@@ -5,8 +5,22 @@ mod search;
 mod watcher;
 mod ffmpeg;
 mod editing;
-
-use db::{Clip, Collection, DbState, SmartFolder, Tag};
+mod patterns;
+mod media;
+mod tz;
+mod filters;
+mod fingerprint;
+mod dupes;
+mod phash;
+mod ingest;
+mod sidecar;
+mod metadata;
+mod stability;
+mod pipeline;
+mod mounts;
+mod thumbjob;
+
+use db::{Clip, Collection, DbState, PatternProfile, SmartFolder, Tag, WatchDir};
 use search::SearchResult;
 use std::path::PathBuf;
 use tauri::{AppHandle, Emitter, Manager};
@@ -18,15 +32,18 @@ struct AppState {
     #[allow(dead_code)]
     app_dir: PathBuf,
     thumbs_dir: PathBuf,
+    playback_dir: PathBuf,
     ffmpeg_path: String,
     ffprobe_path: String,
+    watcher: watcher::WatcherHandle,
+    thumb_job: thumbjob::ThumbJob,
 }
 
 // -- commands --
 
 #[tauri::command]
-async fn scan_clips(state: tauri::State<'_, AppState>) -> Result<Vec<Clip>, String> {
-    scan::scan_dirs(&state.db)
+async fn scan_clips(app: AppHandle, state: tauri::State<'_, AppState>) -> Result<scan::ScanReport, String> {
+    scan::scan_dirs(&state.db, &state.ffprobe_path, &|p| { let _ = app.emit("scan-progress", p); })
 }
 
 #[tauri::command]
@@ -47,17 +64,24 @@ async fn create_tag(state: tauri::State<'_, AppState>, name: String, color: Stri
 
 #[tauri::command]
 async fn delete_tag(state: tauri::State<'_, AppState>, id: String) -> Result<(), String> {
-    state.db.delete_tag(&id)
+    let clip_ids = state.db.get_tag_clip_ids(&id)?;
+    state.db.delete_tag(&id)?;
+    sidecar::sync(&state.db, &clip_ids);
+    Ok(())
 }
 
 #[tauri::command]
 async fn add_tag(state: tauri::State<'_, AppState>, clip_id: String, tag_id: String) -> Result<(), String> {
-    state.db.add_clip_tag(&clip_id, &tag_id)
+    state.db.add_clip_tag(&clip_id, &tag_id)?;
+    sidecar::sync(&state.db, &[clip_id]);
+    Ok(())
 }
 
 #[tauri::command]
 async fn remove_tag(state: tauri::State<'_, AppState>, clip_id: String, tag_id: String) -> Result<(), String> {
-    state.db.remove_clip_tag(&clip_id, &tag_id)
+    state.db.remove_clip_tag(&clip_id, &tag_id)?;
+    sidecar::sync(&state.db, &[clip_id]);
+    Ok(())
 }
 
 #[tauri::command]
@@ -66,19 +90,50 @@ async fn update_description(state: tauri::State<'_, AppState>, clip_id: String,
 
     // re-embed if description is not empty
     if !desc.trim().is_empty() {
-        let vec = simple_embed(&desc);
+        let vec = search::simple_embed(&desc);
         let bytes = search::vec_to_bytes(&vec);
         state.db.upsert_embedding(&clip_id, &bytes)?;
     }
 
+    sidecar::sync(&state.db, &[clip_id]);
     Ok(())
 }
 
 #[tauri::command]
-async fn gen_thumb(state: tauri::State<'_, AppState>, clip_id: String, video_path: String) -> Result<String, String> {
+async fn gen_thumb(state: tauri::State<'_, AppState>, clip_id: String, video_path: String) -> Result<thumbs::Thumbnail, String> {
     thumbs::gen_thumb(&state.db, &clip_id, &video_path, &state.thumbs_dir, &state.ffmpeg_path)
 }
 
+// re-pick a clip's thumbnail, optionally with a different mode than the global one.
+// asking for a new pick drops a chosen cover frame
+#[tauri::command]
+async fn regen_thumb(state: tauri::State<'_, AppState>, clip_id: String, mode: Option<String>) -> Result<thumbs::Thumbnail, String> {
+    let clip = state.db.get_clip(&clip_id)?.ok_or_else(|| format!("clip not found: {}", clip_id))?;
+    if clip.cover_time.is_some() {
+        state.db.set_clip_cover_time(&clip.id, None)?;
+    }
+    let mode = mode.unwrap_or_else(|| thumbs::thumb_mode(&state.db));
+    thumbs::gen_thumb_as(&state.db, &clip.id, &clip.path, &state.thumbs_dir, &state.ffmpeg_path, &mode)
+}
+
+// thumbnail from the frame at `time` seconds, kept through later regeneration
+#[tauri::command]
+async fn set_cover_frame(state: tauri::State<'_, AppState>, clip_id: String, time: Option<f64>) -> Result<thumbs::Thumbnail, String> {
+    thumbs::set_cover(&state.db, &clip_id, &state.thumbs_dir, &state.ffmpeg_path, time)
+}
+
+// tiled hover-scrub frames, built on first request and cached next to the thumbnail
+#[tauri::command]
+async fn get_sprite(state: tauri::State<'_, AppState>, clip_id: String) -> Result<thumbs::SpriteIndex, String> {
+    thumbs::gen_sprite(&state.db, &clip_id, &state.thumbs_dir, &state.ffmpeg_path)
+}
+
+// short silent highlight loop for grid hover, cached like the sprite
+#[tauri::command]
+async fn get_preview_loop(state: tauri::State<'_, AppState>, clip_id: String) -> Result<String, String> {
+    thumbs::gen_preview(&state.db, &clip_id, &state.thumbs_dir, &state.ffmpeg_path)
+}
+
 #[tauri::command]
 async fn gen_all_thumbs(app: AppHandle, state: tauri::State<'_, AppState>) -> Result<(), String> {
     let clips = state.db.get_all_clips()?;
@@ -90,16 +145,15 @@ async fn gen_all_thumbs(app: AppHandle, state: tauri::State<'_, AppState>) -> Re
     }));
 
     for clip in &clips {
-        if clip.thumb_path.is_some() {
+        // offline/missing have nothing to read, recordings aren't finished yet
+        if clip.thumb_path.is_some() || clip.status != "online" {
             done += 1;
             continue;
         }
 
         match thumbs::gen_thumb(&state.db, &clip.id, &clip.path, &state.thumbs_dir, &state.ffmpeg_path) {
-            Ok(thumb_path) => {
-                let _ = app.emit("thumb-ready", serde_json::json!({
-                    "clipId": clip.id, "thumbPath": thumb_path
-                }));
+            Ok(thumb) => {
+                let _ = app.emit("thumb-ready", thumb);
             }
             Err(e) => eprintln!("thumb {}: {}", clip.filename, e),
         }
@@ -127,7 +181,7 @@ async fn gen_all_thumbs(app: AppHandle, state: tauri::State<'_, AppState>) -> Re
 
 #[tauri::command]
 async fn semantic_search(state: tauri::State<'_, AppState>, query: String, limit: usize) -> Result<Vec<SearchResult>, String> {
-    let query_vec = simple_embed(&query);
+    let query_vec = search::simple_embed(&query);
     search::search(&state.db, &query_vec, limit)
 }
 
@@ -143,24 +197,42 @@ async fn open_in_explorer(path: String) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+async fn set_recorded_at(state: tauri::State<'_, AppState>, clip_ids: Vec<String>, recorded_at: i64) -> Result<(), String> {
+    state.db.set_recorded_at(&clip_ids, recorded_at)
+}
+
+#[tauri::command]
+async fn shift_recorded_at(state: tauri::State<'_, AppState>, clip_ids: Vec<String>, offset_secs: i64) -> Result<(), String> {
+    state.db.shift_recorded_at(&clip_ids, offset_secs)
+}
+
 #[tauri::command]
 async fn toggle_star(state: tauri::State<'_, AppState>, clip_id: String, starred: bool) -> Result<(), String> {
-    state.db.toggle_star(&clip_id, starred)
+    state.db.toggle_star(&clip_id, starred)?;
+    sidecar::sync(&state.db, &[clip_id]);
+    Ok(())
 }
 
 #[tauri::command]
 async fn bulk_add_tag(state: tauri::State<'_, AppState>, clip_ids: Vec<String>, tag_id: String) -> Result<(), String> {
-    state.db.bulk_add_tag(&clip_ids, &tag_id)
+    state.db.bulk_add_tag(&clip_ids, &tag_id)?;
+    sidecar::sync(&state.db, &clip_ids);
+    Ok(())
 }
 
 #[tauri::command]
 async fn bulk_remove_tag(state: tauri::State<'_, AppState>, clip_ids: Vec<String>, tag_id: String) -> Result<(), String> {
-    state.db.bulk_remove_tag(&clip_ids, &tag_id)
+    state.db.bulk_remove_tag(&clip_ids, &tag_id)?;
+    sidecar::sync(&state.db, &clip_ids);
+    Ok(())
 }
 
 #[tauri::command]
 async fn bulk_star(state: tauri::State<'_, AppState>, clip_ids: Vec<String>, starred: bool) -> Result<(), String> {
-    state.db.bulk_star(&clip_ids, starred)
+    state.db.bulk_star(&clip_ids, starred)?;
+    sidecar::sync(&state.db, &clip_ids);
+    Ok(())
 }
 
 #[tauri::command]
@@ -176,17 +248,24 @@ async fn create_collection(state: tauri::State<'_, AppState>, name: String, colo
 
 #[tauri::command]
 async fn delete_collection(state: tauri::State<'_, AppState>, id: String) -> Result<(), String> {
-    state.db.delete_collection(&id)
+    let clip_ids = state.db.get_collection_clip_ids(&id)?;
+    state.db.delete_collection(&id)?;
+    sidecar::sync(&state.db, &clip_ids);
+    Ok(())
 }
 
 #[tauri::command]
 async fn add_to_collection(state: tauri::State<'_, AppState>, collection_id: String, clip_ids: Vec<String>) -> Result<(), String> {
-    state.db.add_clips_to_collection(&collection_id, &clip_ids)
+    state.db.add_clips_to_collection(&collection_id, &clip_ids)?;
+    sidecar::sync(&state.db, &clip_ids);
+    Ok(())
 }
 
 #[tauri::command]
 async fn remove_from_collection(state: tauri::State<'_, AppState>, collection_id: String, clip_ids: Vec<String>) -> Result<(), String> {
-    state.db.remove_clips_from_collection(&collection_id, &clip_ids)
+    state.db.remove_clips_from_collection(&collection_id, &clip_ids)?;
+    sidecar::sync(&state.db, &clip_ids);
+    Ok(())
 }
 
 #[tauri::command]
@@ -217,19 +296,232 @@ async fn delete_smart_folder(state: tauri::State<'_, AppState>, id: String) -> R
 
 #[tauri::command]
 async fn get_settings(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let dirs = scan::get_watch_dirs(&state.db);
-    Ok(serde_json::json!({ "watchDirs": dirs }))
+    let dirs = state.db.get_watch_dirs()?;
+    Ok(serde_json::json!({
+        "watchDirs": dirs,
+        "sidecarMode": sidecar::mode(&state.db),
+        "thumbMode": thumbs::thumb_mode(&state.db),
+        "thumbSettings": thumbs::thumb_settings(&state.db),
+        "hoverPreview": thumbs::hover_preview(&state.db),
+    }))
 }
 
 #[tauri::command]
-async fn set_watch_dirs(state: tauri::State<'_, AppState>, dirs: Vec<String>) -> Result<(), String> {
-    let json = serde_json::to_string(&dirs).map_err(|e| e.to_string())?;
-    state.db.set_meta("watch_dirs", &json)
+async fn set_sidecar_mode(state: tauri::State<'_, AppState>, mode: String) -> Result<(), String> {
+    sidecar::set_mode(&state.db, &mode)
+}
+
+#[tauri::command]
+async fn set_thumb_mode(state: tauri::State<'_, AppState>, mode: String) -> Result<(), String> {
+    thumbs::set_thumb_mode(&state.db, &mode)
+}
+
+// new settings make every existing thumbnail stale, regenerate them in the background
+#[tauri::command]
+async fn set_thumb_settings(state: tauri::State<'_, AppState>, settings: thumbs::ThumbSettings) -> Result<(), String> {
+    if thumbs::thumb_settings(&state.db) == settings {
+        return Ok(());
+    }
+    thumbs::set_thumb_settings(&state.db, &settings)?;
+    state.thumb_job.start();
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_hover_preview(state: tauri::State<'_, AppState>, mode: String) -> Result<(), String> {
+    thumbs::set_hover_preview(&state.db, &mode)
+}
+
+#[tauri::command]
+async fn get_thumb_job_status(state: tauri::State<'_, AppState>) -> Result<thumbjob::ThumbJobStatus, String> {
+    Ok(state.thumb_job.status())
+}
+
+#[tauri::command]
+async fn regen_stale_thumbs(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.thumb_job.start();
+    Ok(())
+}
+
+#[tauri::command]
+async fn export_sidecars(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    sidecar::export_all(&state.db)
+}
+
+#[tauri::command]
+async fn import_sidecars(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    sidecar::import_all(&state.db)
+}
+
+#[tauri::command]
+async fn get_watch_dirs(state: tauri::State<'_, AppState>) -> Result<Vec<WatchDir>, String> {
+    state.db.get_watch_dirs()
+}
+
+#[tauri::command]
+async fn add_watch_dir(state: tauri::State<'_, AppState>, path: String, label: Option<String>, color: Option<String>) -> Result<WatchDir, String> {
+    let dir = scan::add_watch_dir(&state.db, &path, label, color)?;
+    state.watcher.reload();
+    Ok(dir)
+}
+
+#[tauri::command]
+async fn update_watch_dir(state: tauri::State<'_, AppState>, dir: WatchDir) -> Result<(), String> {
+    scan::validate_watch_dir(&dir)?;
+    state.db.update_watch_dir(&dir)?;
+    state.watcher.reload();
+    Ok(())
+}
+
+#[tauri::command]
+async fn remove_watch_dir(state: tauri::State<'_, AppState>, id: String) -> Result<(), String> {
+    state.db.delete_watch_dir(&id)?;
+    state.watcher.reload();
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_watcher_status(state: tauri::State<'_, AppState>) -> Result<watcher::WatcherStatus, String> {
+    Ok(state.watcher.status())
+}
+
+#[tauri::command]
+async fn pause_watcher(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.watcher.pause();
+    Ok(())
+}
+
+#[tauri::command]
+async fn resume_watcher(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.watcher.resume();
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_pattern_profiles(state: tauri::State<'_, AppState>) -> Result<Vec<PatternProfile>, String> {
+    state.db.get_pattern_profiles()
+}
+
+#[tauri::command]
+async fn create_pattern_profile(state: tauri::State<'_, AppState>, name: String, regex: String, timestamp_format: String) -> Result<PatternProfile, String> {
+    patterns::validate(&regex, &timestamp_format)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    state.db.create_pattern_profile(&id, &name, &regex, &timestamp_format)
+}
+
+#[tauri::command]
+async fn update_pattern_profile(state: tauri::State<'_, AppState>, id: String, name: String, regex: String, timestamp_format: String) -> Result<(), String> {
+    patterns::validate(&regex, &timestamp_format)?;
+    state.db.update_pattern_profile(&id, &name, &regex, &timestamp_format)
+}
+
+#[tauri::command]
+async fn delete_pattern_profile(state: tauri::State<'_, AppState>, id: String) -> Result<(), String> {
+    state.db.delete_pattern_profile(&id)
 }
 
 #[tauri::command]
 async fn delete_clips(state: tauri::State<'_, AppState>, ids: Vec<String>) -> Result<(), String> {
-    state.db.delete_clips(&ids)
+    state.db.delete_clips(&ids)?;
+    // sprite sheets and preview loops are the bulk of the thumbnail cache
+    for id in &ids {
+        thumbs::remove_cached(&state.thumbs_dir, id);
+    }
+    Ok(())
+}
+
+// drop clips the scanner marked missing, and offline ones too if asked
+#[tauri::command]
+async fn purge_clips(state: tauri::State<'_, AppState>, include_offline: bool) -> Result<usize, String> {
+    let statuses: &[&str] = if include_offline { &["missing", "offline"] } else { &["missing"] };
+    let purged = state.db.purge_clips(statuses)?;
+    for id in &purged {
+        thumbs::remove_cached(&state.thumbs_dir, id);
+    }
+    Ok(purged.len())
+}
+
+#[tauri::command]
+async fn find_duplicates(state: tauri::State<'_, AppState>) -> Result<Vec<dupes::DuplicateGroup>, String> {
+    dupes::find_duplicates(&state.db)
+}
+
+// keep one copy, fold the others' tags/collections/descriptions into it and drop them.
+// the dropped files stay on disk (ignored by future scans) unless delete_files is set
+#[tauri::command]
+async fn resolve_duplicates(state: tauri::State<'_, AppState>, keep_id: String, drop_ids: Vec<String>, delete_files: bool) -> Result<(), String> {
+    let drops = dupes::verify_duplicates(&state.db, &keep_id, &drop_ids)?;
+
+    let desc = state.db.merge_clips(&keep_id, &drop_ids)?;
+    for clip in &drops {
+        thumbs::remove_cached(&state.thumbs_dir, &clip.id);
+    }
+    sidecar::sync(&state.db, std::slice::from_ref(&keep_id));
+    if !desc.trim().is_empty() {
+        let vec = search::simple_embed(&desc);
+        state.db.upsert_embedding(&keep_id, &search::vec_to_bytes(&vec))?;
+    }
+
+    if delete_files {
+        for clip in &drops {
+            if let Err(e) = std::fs::remove_file(&clip.path) {
+                eprintln!("delete duplicate {}: {}", clip.path, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+// paths merged away as duplicates, which scans skip
+#[tauri::command]
+async fn get_ignored_paths(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    let mut paths: Vec<String> = state.db.get_ignored_paths()?.into_iter().collect();
+    paths.sort();
+    Ok(paths)
+}
+
+// let scans pick these paths up again. no paths clears the whole list
+#[tauri::command]
+async fn clear_ignored_paths(state: tauri::State<'_, AppState>, paths: Option<Vec<String>>) -> Result<usize, String> {
+    state.db.delete_ignored_paths(paths.as_deref())
+}
+
+// hash every online clip that doesn't have a perceptual hash yet
+#[tauri::command]
+async fn compute_phashes(app: AppHandle, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let hashed: std::collections::HashSet<String> = state.db.get_all_phashes()?
+        .into_iter().map(|(id, _)| id).collect();
+    let clips: Vec<Clip> = state.db.get_all_clips()?
+        .into_iter()
+        .filter(|c| c.status == "online" && !hashed.contains(&c.id))
+        .collect();
+    let total = clips.len();
+
+    for (done, clip) in clips.iter().enumerate() {
+        if done % 10 == 0 {
+            let _ = app.emit("scan-progress", serde_json::json!({
+                "total": total, "done": done, "phase": "hashing"
+            }));
+        }
+        if let Err(e) = phash::hash_clip(&state.db, &state.ffmpeg_path, &state.ffprobe_path, clip) {
+            eprintln!("phash {}: {}", clip.filename, e);
+        }
+    }
+
+    let _ = app.emit("scan-progress", serde_json::json!({
+        "total": total, "done": total, "phase": "complete"
+    }));
+    Ok(())
+}
+
+#[tauri::command]
+async fn find_similar_clips(state: tauri::State<'_, AppState>, clip_id: String, max_distance: Option<u32>) -> Result<Vec<phash::SimilarClip>, String> {
+    phash::find_similar(&state.db, &state.ffmpeg_path, &state.ffprobe_path, &clip_id, max_distance.unwrap_or(phash::DEFAULT_MAX_DISTANCE))
+}
+
+#[tauri::command]
+async fn ingest_clips(app: AppHandle, state: tauri::State<'_, AppState>, options: ingest::IngestOptions) -> Result<ingest::IngestReport, String> {
+    ingest::ingest(&state.db, &state.ffprobe_path, &options, &|p| { let _ = app.emit("scan-progress", p); })
 }
 
 #[tauri::command]
@@ -247,14 +539,26 @@ async fn probe_clip(state: tauri::State<'_, AppState>, clip_id: String, video_pa
     Ok(serde_json::json!({ "durationSecs": dur, "width": w, "height": h }))
 }
 
+// the source clip's tags/description/star for an edit output, when asked to carry them over
+fn inherited_metadata(db: &DbState, input: &str, inherit: Option<bool>) -> Result<Option<metadata::ClipMetadata>, String> {
+    if inherit.unwrap_or(false) { metadata::for_path(db, input) } else { Ok(None) }
+}
+
 #[tauri::command]
-async fn trim_clip(state: tauri::State<'_, AppState>, input: String, output: String, start: f64, end: f64, precise: bool) -> Result<(), String> {
-    editing::trim_clip(&state.ffmpeg_path, &input, &output, start, end, precise)
+#[allow(clippy::too_many_arguments)]
+async fn trim_clip(state: tauri::State<'_, AppState>, input: String, output: String, start: f64, end: f64, precise: bool, inherit_metadata: Option<bool>) -> Result<(), String> {
+    let meta = inherited_metadata(&state.db, &input, inherit_metadata)?;
+    editing::trim_clip(&state.ffmpeg_path, &input, &output, start, end, precise, meta.as_ref())
 }
 
 #[tauri::command]
-async fn merge_clips(state: tauri::State<'_, AppState>, inputs: Vec<String>, output: String) -> Result<(), String> {
-    editing::merge_clips(&state.ffmpeg_path, &inputs, &output)
+async fn merge_clips(state: tauri::State<'_, AppState>, inputs: Vec<String>, output: String, inherit_metadata: Option<bool>) -> Result<(), String> {
+    // a merge carries the first clip's metadata
+    let meta = match inputs.first() {
+        Some(first) => inherited_metadata(&state.db, first, inherit_metadata)?,
+        None => None,
+    };
+    editing::merge_clips(&state.ffmpeg_path, &inputs, &output, meta.as_ref())
 }
 
 #[tauri::command]
@@ -268,8 +572,42 @@ async fn capture_frame(state: tauri::State<'_, AppState>, input: String, output:
 }
 
 #[tauri::command]
-async fn compress_clip(state: tauri::State<'_, AppState>, input: String, output: String, quality: String, max_width: Option<u32>) -> Result<(), String> {
-    editing::compress_clip(&state.ffmpeg_path, &input, &output, &quality, max_width)
+async fn compress_clip(state: tauri::State<'_, AppState>, input: String, output: String, quality: String, max_width: Option<u32>, inherit_metadata: Option<bool>) -> Result<(), String> {
+    let meta = inherited_metadata(&state.db, &input, inherit_metadata)?;
+    editing::compress_clip(&state.ffmpeg_path, &input, &output, &quality, max_width, meta.as_ref())
+}
+
+// writes tags, description and star into the clips' own container metadata
+#[tauri::command]
+async fn write_clip_metadata(state: tauri::State<'_, AppState>, clip_ids: Vec<String>) -> Result<usize, String> {
+    metadata::write_clips(&state.db, &state.ffmpeg_path, &clip_ids)
+}
+
+// returns a path the webview can play: the original when the container is
+// supported, otherwise a cached mp4 remux (`force` after a failed decode)
+#[tauri::command]
+async fn get_playback_path(state: tauri::State<'_, AppState>, clip_id: String, video_path: String, force: bool) -> Result<String, String> {
+    let src = std::path::Path::new(&video_path);
+    if !force && media::is_playable(src) {
+        return Ok(video_path);
+    }
+
+    // a forced re-encode gets its own cache so the stream copy isn't served again
+    let name = if force { format!("{}.h264.mp4", clip_id) } else { format!("{}.mp4", clip_id) };
+    let out = state.playback_dir.join(name);
+    let fresh = match (std::fs::metadata(&out), std::fs::metadata(src)) {
+        (Ok(o), Ok(s)) => match (o.modified(), s.modified()) {
+            (Ok(om), Ok(sm)) => om >= sm,
+            _ => false,
+        },
+        _ => false,
+    };
+
+    let out_str = out.to_string_lossy().to_string();
+    if !fresh {
+        editing::remux_for_playback(&state.ffmpeg_path, &video_path, &out_str, force)?;
+    }
+    Ok(out_str)
 }
 
 #[tauri::command]
@@ -289,37 +627,6 @@ async fn get_waveform(state: tauri::State<'_, AppState>, clip_id: String, video_
     Ok(bars)
 }
 
-// -- simple embedding (bag-of-words cosine similarity placeholder) --
-fn simple_embed(text: &str) -> Vec<f32> {
-    let mut vec = vec![0.0f32; 384];
-    let lower = text.to_lowercase();
-    for word in lower.split_whitespace() {
-        let mut h = 0u64;
-        for b in word.bytes() {
-            h = h.wrapping_mul(31).wrapping_add(b as u64);
-        }
-        let idx = (h % 384) as usize;
-        vec[idx] += 1.0;
-
-        if word.len() >= 2 {
-            for pair in word.as_bytes().windows(2) {
-                let h2 = (pair[0] as u64).wrapping_mul(31).wrapping_add(pair[1] as u64);
-                let idx2 = (h2 % 384) as usize;
-                vec[idx2] += 0.5;
-            }
-        }
-    }
-
-    let norm: f32 = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
-    if norm > 0.0 {
-        for v in vec.iter_mut() {
-            *v /= norm;
-        }
-    }
-
-    vec
-}
-
 // -- find ffmpeg --
 
 fn find_ffmpeg() -> (String, String) {
@@ -399,6 +706,8 @@ pub fn run() {
                 .or_else(|| uri.strip_prefix("localfile:///"))
                 .or_else(|| uri.strip_prefix("localfile://"))
                 .unwrap_or(&uri);
+            // `?v=` cache busters; a literal ? in a filename arrives encoded
+            let path = path.split('?').next().unwrap_or(path);
 
             let decoded = percent_decode(path);
             let file_path = std::path::Path::new(&decoded);
@@ -407,15 +716,7 @@ pub fn run() {
                 return Response::builder().status(404).body(Vec::new()).unwrap();
             }
 
-            let mime = if decoded.ends_with(".jpg") || decoded.ends_with(".jpeg") {
-                "image/jpeg"
-            } else if decoded.ends_with(".png") {
-                "image/png"
-            } else if decoded.ends_with(".mp4") {
-                "video/mp4"
-            } else {
-                "application/octet-stream"
-            };
+            let mime = media::mime_for(file_path);
 
             let file_size = match std::fs::metadata(file_path) {
                 Ok(m) => m.len(),
@@ -479,19 +780,30 @@ pub fn run() {
             let thumbs_dir = app_dir.join("thumbs");
             std::fs::create_dir_all(&thumbs_dir).ok();
 
+            let playback_dir = app_dir.join("playback");
+            std::fs::create_dir_all(&playback_dir).ok();
+
             let db = DbState::new(app_dir.join("boxy.db"))
                 .map_err(|e| Box::<dyn std::error::Error>::from(e))?;
             db.init().map_err(|e| Box::<dyn std::error::Error>::from(e))?;
+            scan::ensure_default_watch_dir(&db).map_err(|e| Box::<dyn std::error::Error>::from(e))?;
 
-            // spawn file watcher before moving db into state
-            watcher::spawn(app.handle().clone(), db.clone());
+            // spawn the background workers before moving db into state
+            let pipeline = pipeline::Pipeline::spawn(
+                app.handle().clone(), db.clone(), ffmpeg_path.clone(), ffprobe_path.clone(), thumbs_dir.clone(),
+            );
+            let watcher = watcher::spawn(app.handle().clone(), db.clone(), ffprobe_path.clone(), pipeline);
+            let thumb_job = thumbjob::ThumbJob::spawn(app.handle().clone(), db.clone(), ffmpeg_path.clone(), thumbs_dir.clone());
 
             app.manage(AppState {
                 db,
                 app_dir,
                 thumbs_dir,
+                playback_dir,
                 ffmpeg_path: ffmpeg_path.clone(),
                 ffprobe_path: ffprobe_path.clone(),
+                watcher,
+                thumb_job,
             });
 
             Ok(())
@@ -507,14 +819,47 @@ pub fn run() {
             update_description,
             gen_thumb,
             gen_all_thumbs,
+            regen_thumb,
+            set_cover_frame,
+            get_sprite,
+            get_preview_loop,
             semantic_search,
             open_in_explorer,
             check_ffmpeg,
             probe_clip,
             get_settings,
-            set_watch_dirs,
+            get_watch_dirs,
+            add_watch_dir,
+            update_watch_dir,
+            remove_watch_dir,
+            get_watcher_status,
+            pause_watcher,
+            resume_watcher,
+            get_pattern_profiles,
+            create_pattern_profile,
+            update_pattern_profile,
+            delete_pattern_profile,
             delete_clips,
+            purge_clips,
+            find_duplicates,
+            resolve_duplicates,
+            get_ignored_paths,
+            clear_ignored_paths,
+            compute_phashes,
+            find_similar_clips,
+            ingest_clips,
+            set_sidecar_mode,
+            set_thumb_mode,
+            set_thumb_settings,
+            set_hover_preview,
+            get_thumb_job_status,
+            regen_stale_thumbs,
+            export_sidecars,
+            write_clip_metadata,
+            import_sidecars,
             toggle_star,
+            set_recorded_at,
+            shift_recorded_at,
             bulk_add_tag,
             bulk_remove_tag,
             bulk_star,
@@ -534,6 +879,7 @@ pub fn run() {
             capture_frame,
             compress_clip,
             get_waveform,
+            get_playback_path,
         ])
         .run(tauri::generate_context!())
         .expect("error running boxy");
@@ -0,0 +1,57 @@
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Video,
+    Image,
+    // indexes boxy writes next to thumbnails (sprite cues)
+    Text,
+}
+
+pub struct MediaType {
+    pub ext: &'static str,
+    pub mime: &'static str,
+    pub kind: MediaKind,
+    // whether the webview can usually play it directly, otherwise we remux for playback
+    pub playable: bool,
+}
+
+// single source of truth for the file types boxy knows about
+pub const MEDIA_TYPES: &[MediaType] = &[
+    MediaType { ext: "mp4", mime: "video/mp4", kind: MediaKind::Video, playable: true },
+    MediaType { ext: "m4v", mime: "video/mp4", kind: MediaKind::Video, playable: true },
+    MediaType { ext: "mov", mime: "video/quicktime", kind: MediaKind::Video, playable: true },
+    MediaType { ext: "webm", mime: "video/webm", kind: MediaKind::Video, playable: true },
+    MediaType { ext: "mkv", mime: "video/x-matroska", kind: MediaKind::Video, playable: false },
+    MediaType { ext: "avi", mime: "video/x-msvideo", kind: MediaKind::Video, playable: false },
+    MediaType { ext: "flv", mime: "video/x-flv", kind: MediaKind::Video, playable: false },
+    MediaType { ext: "wmv", mime: "video/x-ms-wmv", kind: MediaKind::Video, playable: false },
+    MediaType { ext: "ts", mime: "video/mp2t", kind: MediaKind::Video, playable: false },
+    MediaType { ext: "m2ts", mime: "video/mp2t", kind: MediaKind::Video, playable: false },
+    MediaType { ext: "mts", mime: "video/mp2t", kind: MediaKind::Video, playable: false },
+    MediaType { ext: "jpg", mime: "image/jpeg", kind: MediaKind::Image, playable: true },
+    MediaType { ext: "jpeg", mime: "image/jpeg", kind: MediaKind::Image, playable: true },
+    MediaType { ext: "png", mime: "image/png", kind: MediaKind::Image, playable: true },
+    MediaType { ext: "webp", mime: "image/webp", kind: MediaKind::Image, playable: true },
+    MediaType { ext: "avif", mime: "image/avif", kind: MediaKind::Image, playable: true },
+    MediaType { ext: "gif", mime: "image/gif", kind: MediaKind::Image, playable: true },
+    MediaType { ext: "vtt", mime: "text/vtt", kind: MediaKind::Text, playable: false },
+    MediaType { ext: "json", mime: "application/json", kind: MediaKind::Text, playable: false },
+];
+
+pub fn lookup(path: &Path) -> Option<&'static MediaType> {
+    let ext = path.extension()?.to_str()?;
+    MEDIA_TYPES.iter().find(|t| t.ext.eq_ignore_ascii_case(ext))
+}
+
+pub fn is_video(path: &Path) -> bool {
+    lookup(path).is_some_and(|t| t.kind == MediaKind::Video)
+}
+
+pub fn mime_for(path: &Path) -> &'static str {
+    lookup(path).map(|t| t.mime).unwrap_or("application/octet-stream")
+}
+
+pub fn is_playable(path: &Path) -> bool {
+    lookup(path).is_some_and(|t| t.playable)
+}
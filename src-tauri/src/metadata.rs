@@ -0,0 +1,197 @@
+use crate::db::{Clip, DbState};
+use crate::{ffmpeg, fingerprint, scan};
+use std::collections::HashMap;
+use std::path::Path;
+
+// what boxy knows about a clip, as container tags. empty fields are left out
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClipMetadata {
+    pub title: String,
+    pub description: String,
+    pub keywords: Vec<String>,
+    pub rating: Option<u8>,
+}
+
+impl ClipMetadata {
+    pub fn from_clip(clip: &Clip, tag_names: &HashMap<String, String>) -> Self {
+        let title = Path::new(&clip.filename)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        ClipMetadata {
+            title,
+            description: clip.description.trim().to_string(),
+            keywords: clip.tags.iter().filter_map(|id| tag_names.get(id).cloned()).collect(),
+            // same scale as the xmp sidecar: starred is 5, unstarred carries no rating
+            rating: clip.starred.then_some(5),
+        }
+    }
+}
+
+// `-metadata` pairs for an ffmpeg output. comment and description carry the same
+// text since players disagree on which one they show
+pub fn args(meta: &ClipMetadata, output: &Path) -> Vec<String> {
+    let mut pairs = vec![];
+    if !meta.title.is_empty() {
+        pairs.push(format!("title={}", meta.title));
+    }
+    if !meta.description.is_empty() {
+        pairs.push(format!("comment={}", meta.description));
+        pairs.push(format!("description={}", meta.description));
+    }
+    if !meta.keywords.is_empty() {
+        pairs.push(format!("keywords={}", meta.keywords.join(", ")));
+    }
+    if let Some(rating) = meta.rating {
+        pairs.push(format!("rating={}", rating));
+    }
+
+    let mut args = vec![];
+    for pair in pairs {
+        args.push("-metadata".to_string());
+        args.push(pair);
+    }
+    // mp4/mov only keep a fixed set of itunes atoms unless told to write free-form keys.
+    // other muxers reject the flag outright
+    if matches!(muxer(output), Some("mp4" | "mov")) {
+        args.extend(["-movflags".to_string(), "+use_metadata_tags".to_string()]);
+    }
+    args
+}
+
+// ffmpeg muxer for rewriting a file in place. the temp file doesn't carry a video
+// extension (the watcher would pick it up) so the format has to be named explicitly
+fn muxer(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    match ext.as_str() {
+        "mp4" | "m4v" => Some("mp4"),
+        "mov" => Some("mov"),
+        "mkv" => Some("matroska"),
+        "webm" => Some("webm"),
+        _ => None,
+    }
+}
+
+// rewrite the container's metadata without re-encoding. streams are copied as-is
+pub fn write(ffmpeg_path: &str, path: &Path, meta: &ClipMetadata) -> Result<(), String> {
+    let format = muxer(path)
+        .ok_or_else(|| format!("can't write metadata to {}", path.display()))?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".part");
+    let tmp = std::path::PathBuf::from(tmp);
+    let input = path.to_string_lossy().to_string();
+    let tmp_str = tmp.to_string_lossy().to_string();
+
+    let out = ffmpeg::cmd(ffmpeg_path)
+        .args(["-y", "-i", &input, "-map", "0", "-c", "copy", "-map_metadata", "0"])
+        .args(args(meta, path))
+        // the copied tags may hold a rating from when the clip was starred; an empty value drops it
+        .args(if meta.rating.is_none() { &["-metadata", "rating="][..] } else { &[] })
+        .args(["-f", format, &tmp_str])
+        .output()
+        .map_err(|e| format!("ffmpeg metadata: {}", e))?;
+
+    if !out.status.success() {
+        let _ = std::fs::remove_file(&tmp);
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        return Err(format!("metadata write failed: {}", stderr.chars().take(500).collect::<String>()));
+    }
+    std::fs::rename(&tmp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        format!("replace {}: {}", path.display(), e)
+    })
+}
+
+// metadata for the library clip at `path`, if it is one
+pub fn for_path(db: &DbState, path: &str) -> Result<Option<ClipMetadata>, String> {
+    let Some(clip) = db.get_clip_by_path(path)? else {
+        return Ok(None);
+    };
+    Ok(Some(ClipMetadata::from_clip(&clip, &tag_names(db)?)))
+}
+
+// write tags, description and star into each clip's file. the new stat and
+// fingerprint are recorded so the next scan doesn't treat the rewrite as new content.
+// returns how many files were written
+pub fn write_clips(db: &DbState, ffmpeg_path: &str, clip_ids: &[String]) -> Result<usize, String> {
+    let tag_names = tag_names(db)?;
+    let mut written = 0;
+    for id in clip_ids {
+        let Some(clip) = db.get_clip(id)? else { continue };
+        if clip.status != "online" {
+            continue;
+        }
+        let path = Path::new(&clip.path);
+        let result = write(ffmpeg_path, path, &ClipMetadata::from_clip(&clip, &tag_names)).and_then(|_| {
+            let stat = std::fs::metadata(path).map_err(|e| e.to_string())?;
+            let mtime = scan::mtime_secs(&stat).unwrap_or_default();
+            let fp = fingerprint::fingerprint(path).ok();
+            db.update_clip_file_identity(&clip.id, stat.len() as i64, mtime, fp.as_deref())
+        });
+        match result {
+            Ok(()) => written += 1,
+            Err(e) => eprintln!("metadata {}: {}", clip.path, e),
+        }
+    }
+    Ok(written)
+}
+
+fn tag_names(db: &DbState) -> Result<HashMap<String, String>, String> {
+    Ok(db.get_all_tags()?.into_iter().map(|t| (t.id, t.name)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args() {
+        let meta = ClipMetadata {
+            title: "clutch".into(),
+            description: String::new(),
+            keywords: vec!["ace".into(), "ranked".into()],
+            rating: Some(5),
+        };
+        let args = args(&meta, Path::new("clutch.mp4"));
+        assert!(args.contains(&"title=clutch".to_string()));
+        assert!(args.contains(&"keywords=ace, ranked".to_string()));
+        assert!(args.contains(&"rating=5".to_string()));
+        assert!(!args.iter().any(|a| a.starts_with("comment=")));
+        assert!(args.contains(&"+use_metadata_tags".to_string()));
+        assert!(!super::args(&meta, Path::new("clutch.mkv")).contains(&"-movflags".to_string()));
+
+        // unstarred clips don't get a rating stamped on
+        let mut clip = Clip {
+            id: "c1".into(),
+            filename: "clutch.mp4".into(),
+            path: "/clips/clutch.mp4".into(),
+            dir_source: "clips".into(),
+            watch_dir_id: None,
+            recorded_at: 0,
+            recorded_at_source: "filename".into(),
+            file_size: 1,
+            file_mtime: None,
+            fingerprint: None,
+            duration_secs: None,
+            width: None,
+            height: None,
+            thumb_path: None,
+            thumb_detail_path: None,
+            cover_time: None,
+            description: String::new(),
+            tags: vec![],
+            starred: false,
+            status: "online".into(),
+            created_at: 0,
+            updated_at: 0,
+        };
+        let meta = ClipMetadata::from_clip(&clip, &HashMap::new());
+        assert_eq!(meta.rating, None);
+        assert!(!super::args(&meta, Path::new("clutch.mp4")).iter().any(|a| a.starts_with("rating=")));
+        clip.starred = true;
+        assert_eq!(ClipMetadata::from_clip(&clip, &HashMap::new()).rating, Some(5));
+
+        assert_eq!(muxer(Path::new("a.MP4")), Some("mp4"));
+        assert_eq!(muxer(Path::new("a.avi")), None);
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+
+// filesystems where native change events miss writes made by other machines (or
+// never arrive at all), so the watcher has to poll them
+fn is_remote_fstype(fstype: &str) -> bool {
+    let fstype = fstype.to_lowercase();
+    // fuseblk is a local disk (ntfs-3g, exfat) and notifies fine
+    if fstype == "fuseblk" {
+        return false;
+    }
+    fstype.starts_with("nfs")
+        || fstype.starts_with("smb")
+        || fstype.starts_with("fuse")
+        || fstype.ends_with("fuse")
+        || matches!(fstype.as_str(), "cifs" | "afpfs" | "webdav" | "davfs" | "9p" | "ceph" | "glusterfs" | "sshfs")
+}
+
+// /proc/self/mounts: `device mountpoint fstype options ...`, spaces in the mount
+// point escaped as \040
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_mounts(table: &str) -> Vec<(PathBuf, String)> {
+    table.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount = fields.nth(1)?.replace("\\040", " ").replace("\\011", "\t");
+            let fstype = fields.next()?;
+            Some((PathBuf::from(mount), fstype.to_string()))
+        })
+        .collect()
+}
+
+// `mount` on macos: `device on /mount/point (fstype, options...)`
+#[cfg(any(target_os = "macos", test))]
+fn parse_mount_output(out: &str) -> Vec<(PathBuf, String)> {
+    out.lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount, opts) = rest.rsplit_once(" (")?;
+            let fstype = opts.split([',', ')']).next()?.trim();
+            Some((PathBuf::from(mount), fstype.to_string()))
+        })
+        .collect()
+}
+
+// fstype of the longest mount point containing `path`
+fn fstype_for(mounts: &[(PathBuf, String)], path: &Path) -> Option<String> {
+    mounts.iter()
+        .filter(|(mount, _)| path.starts_with(mount))
+        .max_by_key(|(mount, _)| mount.as_os_str().len())
+        .map(|(_, fstype)| fstype.clone())
+}
+
+#[cfg(target_os = "linux")]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    std::fs::read_to_string("/proc/self/mounts")
+        .map(|t| parse_proc_mounts(&t))
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    std::process::Command::new("mount")
+        .output()
+        .map(|o| parse_mount_output(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    vec![]
+}
+
+// network share or fuse mount. on windows only unc paths are recognised; mapped
+// drives that don't notify are caught by the watch failing instead
+pub fn is_remote(path: &Path) -> bool {
+    if cfg!(windows) && path.to_string_lossy().starts_with(r"\\") {
+        return true;
+    }
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    fstype_for(&mount_table(), &path).is_some_and(|t| is_remote_fstype(&t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fstype_for() {
+        let proc = "/dev/sda1 / ext4 rw 0 0\n\
+                    //nas/captures /mnt/nas\\040share cifs rw 0 0\n\
+                    sshfs#me@box: /home/me/box fuse.sshfs rw 0 0\n\
+                    /dev/sdb1 /media/usb fuseblk rw 0 0\n";
+        let mounts = parse_proc_mounts(proc);
+        let remote = |p: &str| fstype_for(&mounts, Path::new(p)).is_some_and(|t| is_remote_fstype(&t));
+        assert!(remote("/mnt/nas share/OBS"));
+        assert!(remote("/home/me/box/clips"));
+        assert!(!remote("/home/me/Videos"));
+        assert!(!remote("/media/usb/clips"));
+
+        let mac = "/dev/disk3s1 on / (apfs, local, journaled)\n\
+                   //me@nas/Captures on /Volumes/Captures (smbfs, nodev, nosuid, mounted by me)\n";
+        let mounts = parse_mount_output(mac);
+        assert_eq!(fstype_for(&mounts, Path::new("/Volumes/Captures/a")).as_deref(), Some("smbfs"));
+        assert_eq!(fstype_for(&mounts, Path::new("/Users/me")).as_deref(), Some("apfs"));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::db::PatternProfile;
+use chrono::NaiveDateTime;
+use regex::Regex;
+
+pub const DEFAULT_PROFILE: &str = "obs";
+
+// built-in profiles: (id, name, regex, timestamp format)
+// regexes run against the file stem; the `ts` group is handed to chrono with
+// underscores normalized to spaces (a space in the format matches any/no whitespace)
+pub const BUILTIN_PROFILES: &[(&str, &str, &str, &str)] = &[
+    (
+        "obs",
+        "OBS Studio",
+        r"^(?:Replay )?(?P<ts>\d{4}-\d{2}-\d{2}[_ ]\d{2}-\d{2}-\d{2})$",
+        "%Y-%m-%d %H-%M-%S",
+    ),
+    (
+        "shadowplay",
+        "NVIDIA ShadowPlay",
+        r"^.+ (?P<ts>\d{4}\.\d{2}\.\d{2} - \d{2}\.\d{2}\.\d{2})\.\d{2}(?:\.DVR)?$",
+        "%Y.%m.%d - %H.%M.%S",
+    ),
+    (
+        "steam",
+        "Steam / game-prefixed",
+        r"^.+?[ _-]+(?P<ts>\d{4}-\d{2}-\d{2}[_ ]\d{2}-\d{2}-\d{2})$",
+        "%Y-%m-%d %H-%M-%S",
+    ),
+    (
+        "medal",
+        "Medal.tv",
+        r"^MedalTV.*?(?P<ts>\d{14})$",
+        "%Y%m%d%H%M%S",
+    ),
+    (
+        "phone",
+        "Phone (Android / Pixel)",
+        r"^(?:VID|PXL)_(?P<ts>\d{8}_\d{6})",
+        "%Y%m%d %H%M%S",
+    ),
+    (
+        "camera",
+        "Camera (DJI / generic)",
+        r"^(?:[A-Za-z]+_)?(?P<ts>\d{8}_?\d{6})(?:[_.-].*)?$",
+        "%Y%m%d %H%M%S",
+    ),
+    (
+        "all",
+        "Match everything",
+        r"^.*$",
+        "",
+    ),
+];
+
+// compiled pattern profile
+pub struct Matcher {
+    re: Regex,
+    format: String,
+}
+
+impl Matcher {
+    pub fn new(profile: &PatternProfile) -> Result<Self, String> {
+        let re = Regex::new(&profile.regex).map_err(|e| format!("profile {}: {}", profile.id, e))?;
+        Ok(Self { re, format: profile.timestamp_format.clone() })
+    }
+
+    pub fn is_match(&self, stem: &str) -> bool {
+        self.re.is_match(stem)
+    }
+
+    // false for catch-all profiles that don't carry a timestamp
+    pub fn has_timestamp(&self) -> bool {
+        !self.format.is_empty()
+    }
+
+    pub fn timestamp(&self, stem: &str) -> Option<NaiveDateTime> {
+        if !self.has_timestamp() { return None; }
+        let caps = self.re.captures(stem)?;
+        let raw = caps.name("ts")?.as_str().replace('_', " ");
+        NaiveDateTime::parse_from_str(&raw, &self.format).ok()
+    }
+}
+
+// check a user-supplied profile before it goes into the db
+pub fn validate(regex: &str, timestamp_format: &str) -> Result<(), String> {
+    let re = Regex::new(regex).map_err(|e| format!("invalid regex: {}", e))?;
+    if !timestamp_format.is_empty() && !re.capture_names().any(|n| n == Some("ts")) {
+        return Err("regex needs a (?P<ts>...) group when a timestamp format is set".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builtin(id: &str) -> Matcher {
+        let (id, name, regex, fmt) = BUILTIN_PROFILES.iter().find(|p| p.0 == id).unwrap();
+        Matcher::new(&PatternProfile {
+            id: id.to_string(),
+            name: name.to_string(),
+            regex: regex.to_string(),
+            timestamp_format: fmt.to_string(),
+            builtin: true,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap()
+    }
+
+    fn ts(m: &Matcher, stem: &str) -> Option<String> {
+        m.timestamp(stem).map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+    }
+
+    #[test]
+    fn test_builtin_profiles() {
+        let obs = builtin("obs");
+        assert_eq!(ts(&obs, "2026-01-28 18-40-28").as_deref(), Some("2026-01-28 18:40:28"));
+        assert_eq!(ts(&obs, "2026-02-17_14-36-20").as_deref(), Some("2026-02-17 14:36:20"));
+        assert_eq!(ts(&obs, "Replay 2026-02-17 14-36-20").as_deref(), Some("2026-02-17 14:36:20"));
+        assert!(!obs.is_match("VID_20240128_184028"));
+
+        let sp = builtin("shadowplay");
+        assert_eq!(ts(&sp, "Counter-strike 2 2024.01.28 - 18.40.28.02.DVR").as_deref(), Some("2024-01-28 18:40:28"));
+
+        let steam = builtin("steam");
+        assert_eq!(ts(&steam, "Elden Ring - 2024-06-27 20-21-04").as_deref(), Some("2024-06-27 20:21:04"));
+
+        let medal = builtin("medal");
+        assert_eq!(ts(&medal, "MedalTVValorant20240128184028").as_deref(), Some("2024-01-28 18:40:28"));
+
+        let phone = builtin("phone");
+        assert_eq!(ts(&phone, "VID_20240128_184028").as_deref(), Some("2024-01-28 18:40:28"));
+        assert_eq!(ts(&phone, "PXL_20240128_184028123").as_deref(), Some("2024-01-28 18:40:28"));
+
+        let cam = builtin("camera");
+        assert_eq!(ts(&cam, "DJI_20240128184028_0001_D").as_deref(), Some("2024-01-28 18:40:28"));
+        assert_eq!(ts(&cam, "20240128_184028").as_deref(), Some("2024-01-28 18:40:28"));
+
+        let all = builtin("all");
+        assert!(all.is_match("IMG_1234"));
+        assert!(!all.has_timestamp());
+        assert_eq!(all.timestamp("IMG_1234"), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(r"^(?P<ts>\d{8})$", "%Y%m%d").is_ok());
+        assert!(validate(r"^.*$", "").is_ok());
+        assert!(validate(r"^\d{8}$", "%Y%m%d").is_err());
+        assert!(validate(r"^(unclosed", "").is_err());
+    }
+}
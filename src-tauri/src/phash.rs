@@ -0,0 +1,168 @@
+use crate::db::{Clip, DbState};
+use crate::ffmpeg;
+use crate::thumbs;
+use serde::Serialize;
+
+// one frame every SAMPLE_SECS of footage, at the same absolute times in every clip so a
+// trimmed copy's frames line up with the original's. capped so long recordings stay cheap
+const SAMPLE_SECS: f64 = 2.0;
+const MAX_FRAMES: usize = 150;
+// default hamming threshold on the 64-bit frame hashes
+pub const DEFAULT_MAX_DISTANCE: u32 = 10;
+
+// difference hash of a 9x8 grayscale frame: one bit per horizontal neighbour pair
+pub fn dhash(gray: &[u8]) -> u64 {
+    let mut hash = 0u64;
+    for row in gray.chunks_exact(9).take(8) {
+        for pair in row.windows(2) {
+            hash = (hash << 1) | (pair[0] > pair[1]) as u64;
+        }
+    }
+    hash
+}
+
+// hash a clip and store the result. clips that haven't been probed yet are probed
+// first; sampling against a guessed duration would hash the wrong frames
+pub fn hash_clip(db: &DbState, ffmpeg_path: &str, ffprobe_path: &str, clip: &Clip) -> Result<Vec<u64>, String> {
+    let duration = match clip.duration_secs.filter(|d| *d > 0.0) {
+        Some(d) => d,
+        None => {
+            let (dur, w, h) = thumbs::probe_meta(&clip.path, ffprobe_path)?;
+            db.update_clip_meta(&clip.id, dur, w, h)?;
+            Some(dur).filter(|d| *d > 0.0).ok_or_else(|| format!("no duration for {}", clip.filename))?
+        }
+    };
+    let hashes = compute(ffmpeg_path, &clip.path, duration)?;
+    db.save_phash(&clip.id, &to_bytes(&hashes))?;
+    Ok(hashes)
+}
+
+// dHash one frame every SAMPLE_SECS, in a single decode pass. survives re-encodes, scaling
+// and bitrate changes; a trimmed copy hashes to a run of the original's sequence, give or
+// take where the trim point falls between two samples
+fn compute(ffmpeg_path: &str, video_path: &str, duration: f64) -> Result<Vec<u64>, String> {
+    // start half an interval in, avoids black first frames
+    let start = (SAMPLE_SECS / 2.0).min(duration / 2.0);
+    let span = (duration - start).min(SAMPLE_SECS * MAX_FRAMES as f64);
+    let output = ffmpeg::cmd(ffmpeg_path)
+        .args([
+            "-v", "quiet",
+            "-ss", &format!("{:.3}", start),
+            "-i", video_path,
+            "-t", &format!("{:.3}", span),
+            "-an",
+            "-vf", &format!("fps=1/{},scale=9:8:flags=area,format=gray", SAMPLE_SECS),
+            "-f", "rawvideo",
+            "-",
+        ])
+        .output()
+        .map_err(|e| format!("ffmpeg: {}", e))?;
+
+    let hashes: Vec<u64> = output.stdout.chunks_exact(72).take(MAX_FRAMES).map(dhash).collect();
+    if hashes.is_empty() {
+        return Err("ffmpeg failed to extract frames".into());
+    }
+    Ok(hashes)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarClip {
+    pub clip_id: String,
+    pub distance: u32,
+}
+
+// clips that look like `clip_id`, closest first. only compares against clips that
+// already have hashes; the target is hashed on demand
+pub fn find_similar(db: &DbState, ffmpeg_path: &str, ffprobe_path: &str, clip_id: &str, max_distance: u32) -> Result<Vec<SimilarClip>, String> {
+    let mut all: Vec<(String, Vec<u64>)> = db.get_all_phashes()?
+        .into_iter()
+        .map(|(id, bytes)| (id, from_bytes(&bytes)))
+        .collect();
+
+    let target = match all.iter().position(|(id, _)| id == clip_id) {
+        Some(i) => all.swap_remove(i).1,
+        None => {
+            let clip = db.get_clip(clip_id)?.ok_or("clip not found")?;
+            hash_clip(db, ffmpeg_path, ffprobe_path, &clip)?
+        }
+    };
+
+    let mut similar: Vec<SimilarClip> = all.into_iter()
+        .map(|(id, hashes)| SimilarClip { distance: distance(&target, &hashes), clip_id: id })
+        .filter(|s| s.distance <= max_distance)
+        .collect();
+    similar.sort_by_key(|s| s.distance);
+    Ok(similar)
+}
+
+pub fn to_bytes(hashes: &[u64]) -> Vec<u8> {
+    hashes.iter().flat_map(|h| h.to_le_bytes()).collect()
+}
+
+pub fn from_bytes(bytes: &[u8]) -> Vec<u64> {
+    bytes.chunks_exact(8)
+        .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+        .collect()
+}
+
+// slide the shorter sequence along the longer one and keep the best alignment's average
+// frame distance. a trim of a longer clip lines up with the stretch it was cut from
+pub fn distance(a: &[u64], b: &[u64]) -> u32 {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if short.is_empty() { return u64::BITS; }
+
+    (0..=long.len() - short.len())
+        .map(|offset| {
+            let total: u32 = short.iter().zip(&long[offset..])
+                .map(|(x, y)| (x ^ y).count_ones())
+                .sum();
+            total / short.len() as u32
+        })
+        .min()
+        .unwrap_or(u64::BITS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(offset: u8) -> Vec<u8> {
+        (0..72).map(|i| ((i % 9) as u8 * 20).wrapping_add(offset)).collect()
+    }
+
+    #[test]
+    fn test_dhash() {
+        // rising left to right: no neighbour is brighter than the next
+        assert_eq!(dhash(&gradient(0)), 0);
+        // uniform brightness shift (re-encode, gamma) doesn't move the hash
+        assert_eq!(dhash(&gradient(0)), dhash(&gradient(10)));
+
+        let falling: Vec<u8> = gradient(0).into_iter().rev().collect();
+        assert_eq!(dhash(&falling), u64::MAX);
+    }
+
+    #[test]
+    fn test_distance() {
+        let original = [0x0f0f, 0xff00, 0x1234_5678, 0xdead_beef];
+        let trimmed = [0xff00, 0x1234_5679];
+        assert_eq!(distance(&original, &trimmed), 0);
+        assert_eq!(distance(&trimmed, &original), 0);
+        // same frames out of order aren't the same footage
+        assert!(distance(&original, &[0x1234_5678, 0xff00]) > 0);
+        assert_eq!(distance(&[0], &[u64::MAX]), 64);
+        assert_eq!(from_bytes(&to_bytes(&original)), original);
+    }
+
+    #[test]
+    fn test_distance_trimmed_sequence() {
+        // a 30s clip and a copy trimmed to its middle 10s, re-encoded so a bit or two flips
+        let original: Vec<u64> = (0..15u64).map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15)).collect();
+        let trimmed: Vec<u64> = original[6..11].iter().map(|h| h ^ 1).collect();
+        assert_eq!(distance(&original, &trimmed), 1);
+
+        // a different clip of the same length doesn't come close
+        let other: Vec<u64> = (0..5u64).map(|i| !i.wrapping_mul(0x9e37_79b9_7f4a_7c15)).collect();
+        assert!(distance(&original, &other) > DEFAULT_MAX_DISTANCE);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::db::{Clip, DbState};
+use crate::{editing, fingerprint, search, thumbs};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+
+// clips waiting for processing. past this the watcher drops work instead of
+// piling up; gen_all_thumbs from the UI still backfills anything missed
+const QUEUE_LEN: usize = 512;
+// same resolution get_waveform caches
+const WAVEFORM_BARS: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepEvent {
+    pub clip_id: String,
+    // probe, thumbnail, waveform, fingerprint, embedding
+    pub step: &'static str,
+    pub error: Option<String>,
+}
+
+struct Worker {
+    app: AppHandle,
+    db: DbState,
+    ffmpeg_path: String,
+    ffprobe_path: String,
+    thumbs_dir: PathBuf,
+}
+
+// probe, thumbnail, waveform, fingerprint and embedding for new clips, one clip at a
+// time on a background thread. cheap to clone
+#[derive(Clone)]
+pub struct Pipeline {
+    tx: SyncSender<String>,
+    queued: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Pipeline {
+    pub fn spawn(app: AppHandle, db: DbState, ffmpeg_path: String, ffprobe_path: String, thumbs_dir: PathBuf) -> Self {
+        let (tx, rx) = mpsc::sync_channel(QUEUE_LEN);
+        let queued = Arc::new(Mutex::new(HashSet::new()));
+        let worker = Worker { app, db, ffmpeg_path, ffprobe_path, thumbs_dir };
+        let worker_queued = queued.clone();
+        std::thread::spawn(move || worker.run(rx, &worker_queued));
+        Pipeline { tx, queued }
+    }
+
+    pub fn enqueue(&self, clip_id: &str) {
+        // already waiting, the worker reads the clip fresh when it gets to it
+        if !self.queued.lock().insert(clip_id.to_string()) { return; }
+        match self.tx.try_send(clip_id.to_string()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(id)) | Err(TrySendError::Disconnected(id)) => {
+                eprintln!("pipeline: can't queue {}, skipping", id);
+                self.queued.lock().remove(&id);
+            }
+        }
+    }
+
+    // every online clip that is still missing probe results or a thumbnail
+    pub fn enqueue_pending(&self, db: &DbState) {
+        match db.get_all_clips() {
+            Ok(clips) => clips.iter().filter(|c| needs_work(c)).for_each(|c| self.enqueue(&c.id)),
+            Err(e) => eprintln!("pipeline: {}", e),
+        }
+    }
+}
+
+pub fn needs_work(clip: &Clip) -> bool {
+    clip.status == "online" && (clip.thumb_path.is_none() || clip.duration_secs.is_none())
+}
+
+impl Worker {
+    fn run(self, rx: Receiver<String>, queued: &Mutex<HashSet<String>>) {
+        for clip_id in rx {
+            queued.lock().remove(&clip_id);
+            match self.db.get_clip(&clip_id) {
+                Ok(Some(clip)) if clip.status == "online" => self.process(&clip),
+                Ok(_) => {}
+                Err(e) => eprintln!("pipeline {}: {}", clip_id, e),
+            }
+        }
+    }
+
+    fn process(&self, clip: &Clip) {
+        if clip.duration_secs.is_none() {
+            let result = thumbs::probe_meta(&clip.path, &self.ffprobe_path).and_then(|(dur, w, h)| {
+                self.db.update_clip_meta(&clip.id, dur, w, h)?;
+                let _ = self.app.emit("meta-ready", serde_json::json!({
+                    "clipId": clip.id, "durationSecs": dur, "width": w, "height": h
+                }));
+                Ok(())
+            });
+            self.step(clip, "probe", result);
+        }
+
+        if clip.thumb_path.is_none() {
+            let result = thumbs::gen_thumb(&self.db, &clip.id, &clip.path, &self.thumbs_dir, &self.ffmpeg_path)
+                .map(|thumb| {
+                    let _ = self.app.emit("thumb-ready", thumb);
+                });
+            self.step(clip, "thumbnail", result);
+        }
+
+        if !matches!(self.db.get_waveform(&clip.id), Ok(Some(_))) {
+            let result = editing::generate_waveform(&self.ffmpeg_path, &clip.path, WAVEFORM_BARS).and_then(|bars| {
+                let bytes: Vec<u8> = bars.iter().flat_map(|f| f.to_le_bytes()).collect();
+                self.db.save_waveform(&clip.id, &bytes, bars.len() as i32)
+            });
+            self.step(clip, "waveform", result);
+        }
+
+        if clip.fingerprint.is_none() {
+            let result = fingerprint::fingerprint(Path::new(&clip.path))
+                .and_then(|fp| self.db.update_clip_fingerprint(&clip.id, &fp));
+            self.step(clip, "fingerprint", result);
+        }
+
+        // descriptions can arrive with the clip through a sidecar
+        if !clip.description.trim().is_empty() {
+            let vec = search::simple_embed(&clip.description);
+            let result = self.db.upsert_embedding(&clip.id, &search::vec_to_bytes(&vec));
+            self.step(clip, "embedding", result);
+        }
+    }
+
+    fn step(&self, clip: &Clip, step: &'static str, result: Result<(), String>) {
+        if let Err(e) = &result {
+            eprintln!("pipeline {} {}: {}", step, clip.filename, e);
+        }
+        let _ = self.app.emit("pipeline-step", StepEvent {
+            clip_id: clip.id.clone(),
+            step,
+            error: result.err(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_work() {
+        let mut clip = Clip {
+            id: "c1".into(),
+            filename: "a.mp4".into(),
+            path: "/clips/a.mp4".into(),
+            dir_source: "clips".into(),
+            watch_dir_id: None,
+            recorded_at: 0,
+            recorded_at_source: "filename".into(),
+            file_size: 1,
+            file_mtime: None,
+            fingerprint: None,
+            duration_secs: None,
+            width: None,
+            height: None,
+            thumb_path: None,
+            thumb_detail_path: None,
+            cover_time: None,
+            description: String::new(),
+            tags: vec![],
+            starred: false,
+            status: "online".into(),
+            created_at: 0,
+            updated_at: 0,
+        };
+        assert!(needs_work(&clip));
+        clip.duration_secs = Some(3.0);
+        clip.thumb_path = Some("t.jpg".into());
+        assert!(!needs_work(&clip));
+        // placeholders wait until the recording settles
+        clip.thumb_path = None;
+        clip.status = "recording".into();
+        assert!(!needs_work(&clip));
+    }
+}
@@ -1,111 +1,846 @@
-use crate::db::{Clip, DbState};
-use chrono::NaiveDateTime;
-use regex::Regex;
-use std::path::Path;
+use crate::db::{Clip, DbState, WatchDir, WATCH_DIR_COLORS, WATCH_MODES};
+use crate::filters::DirFilter;
+use crate::fingerprint;
+use crate::media;
+use crate::patterns::{self, Matcher};
+use crate::search;
+use crate::sidecar;
+use crate::stability;
+use crate::thumbs;
+use crate::tz;
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-pub fn get_watch_dirs(db: &DbState) -> Vec<String> {
-    if let Ok(Some(json)) = db.get_meta("watch_dirs") {
-        if let Ok(dirs) = serde_json::from_str::<Vec<String>>(&json) {
-            if !dirs.is_empty() { return dirs; }
+// first run: seed the OS videos folder so there's something to scan
+pub fn ensure_default_watch_dir(db: &DbState) -> Result<(), String> {
+    if db.get_meta("watch_dirs_seeded")?.is_some() { return Ok(()); }
+    if db.get_watch_dirs()?.is_empty() {
+        if let Some(videos) = dirs::video_dir() {
+            add_watch_dir(db, &videos.to_string_lossy(), None, None)?;
         }
     }
-    // fallback: default videos dir
-    dirs::video_dir()
-        .map(|p| vec![p.to_string_lossy().to_string()])
-        .unwrap_or_default()
+    db.set_meta("watch_dirs_seeded", "1")
 }
 
-// parse timestamp from filename like "2026-01-28 18-40-28.mp4" or "2026-02-17_14-36-20.mp4"
-fn parse_timestamp(filename: &str) -> Option<i64> {
-    let stem = filename.strip_suffix(".mp4")?;
-    let normalized = stem.replace('_', " ");
-    let dt = NaiveDateTime::parse_from_str(&normalized, "%Y-%m-%d %H-%M-%S").ok()?;
-    Some(dt.and_utc().timestamp())
+pub fn add_watch_dir(db: &DbState, path: &str, label: Option<String>, color: Option<String>) -> Result<WatchDir, String> {
+    if !Path::new(path).is_dir() {
+        return Err(format!("not a directory: {}", path));
+    }
+    let count = db.get_watch_dirs()?.len();
+    let now = chrono::Utc::now().timestamp();
+    let dir = WatchDir {
+        id: uuid::Uuid::new_v4().to_string(),
+        path: path.to_string(),
+        label: label.filter(|l| !l.trim().is_empty()).unwrap_or_else(|| {
+            Path::new(path).file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string())
+        }),
+        color: color.unwrap_or_else(|| WATCH_DIR_COLORS[count % WATCH_DIR_COLORS.len()].to_string()),
+        max_depth: Some(2),
+        include_globs: vec![],
+        exclude_globs: vec![],
+        enabled: true,
+        profile_id: None,
+        timezone: None,
+        watch_mode: "auto".into(),
+        poll_interval_secs: None,
+        created_at: now,
+        updated_at: now,
+    };
+    db.create_watch_dir(&dir)?;
+    // clips left behind by a removed dir (or from before watch dirs) rejoin it
+    db.link_unowned_clips()?;
+    Ok(dir)
 }
 
-pub fn scan_dirs(db: &DbState) -> Result<Vec<Clip>, String> {
-    let re = Regex::new(r"^\d{4}-\d{2}-\d{2}[_ ]\d{2}-\d{2}-\d{2}\.mp4$").unwrap();
-    let now = chrono::Utc::now().timestamp();
-    let watch_dirs = get_watch_dirs(db);
+pub fn validate_watch_dir(dir: &WatchDir) -> Result<(), String> {
+    if dir.label.trim().is_empty() {
+        return Err("label can't be empty".into());
+    }
+    if let Some(name) = &dir.timezone {
+        tz::parse_zone(name)?;
+    }
+    if !WATCH_MODES.contains(&dir.watch_mode.as_str()) {
+        return Err(format!("unknown watch mode: {}", dir.watch_mode));
+    }
+    if dir.poll_interval_secs == Some(0) {
+        return Err("poll interval must be at least 1 second".into());
+    }
+    DirFilter::new(dir)?;
+    Ok(())
+}
 
-    let mut seen_paths = std::collections::HashSet::new();
+// recorded_at fallback chain: filename pattern, container creation_time, file birth/mtime.
+// no ffprobe skips the container step, for files still being written
+pub fn resolve_recorded_at(
+    matcher: &Matcher,
+    stem: &str,
+    path: &Path,
+    meta: Option<&Metadata>,
+    zone: Option<Tz>,
+    ffprobe_path: Option<&str>,
+) -> Option<(i64, &'static str)> {
+    if let Some(dt) = matcher.timestamp(stem) {
+        return Some((tz::local_to_utc(dt, zone), "filename"));
+    }
+
+    if let Some(ts) = ffprobe_path.and_then(|ffprobe| thumbs::probe_creation_time(&path.to_string_lossy(), ffprobe)) {
+        return Some((ts, "metadata"));
+    }
+
+    let meta = meta?;
+    let time = meta.created().or_else(|_| meta.modified()).ok()?;
+    Some((chrono::DateTime::<chrono::Utc>::from(time).timestamp(), "filetime"))
+}
+
+pub fn mtime_secs(meta: &Metadata) -> Option<i64> {
+    let time = meta.modified().ok()?;
+    Some(chrono::DateTime::<chrono::Utc>::from(time).timestamp())
+}
+
+// a file rewritten in place (re-encode, overwrite, trim-over-original). everything
+// derived from the old bytes is dropped; the thumbnail is regenerated by the next
+// gen_all_thumbs pass, the waveform on next request
+fn refresh_changed_clip(db: &DbState, clip: &Clip, size: i64, mtime: i64, ffprobe_path: &str) {
+    let fp = fingerprint::fingerprint(Path::new(&clip.path)).ok();
+    if let Err(e) = db.invalidate_clip_content(&clip.id, size, mtime, fp.as_deref()) {
+        eprintln!("invalidate clip {}: {}", clip.id, e);
+        return;
+    }
+    for thumb in [&clip.thumb_path, &clip.thumb_detail_path].into_iter().flatten() {
+        let _ = std::fs::remove_file(thumb);
+    }
+    if let Ok((dur, w, h)) = thumbs::probe_meta(&clip.path, ffprobe_path) {
+        let _ = db.update_clip_meta(&clip.id, dur, w, h);
+    }
+    // the embedding only depends on the description, rebuild it right away
+    if !clip.description.trim().is_empty() {
+        let vec = search::simple_embed(&clip.description);
+        let _ = db.upsert_embedding(&clip.id, &search::vec_to_bytes(&vec));
+    }
+}
+
+// the pattern and timezone used to date clips under a watch dir
+fn dating<'a>(matchers: &'a HashMap<String, Matcher>, dir: Option<&WatchDir>) -> Option<(&'a Matcher, Option<Tz>)> {
+    let dir = dir?;
+    let zone = dir.timezone.as_deref().and_then(|name| tz::parse_zone(name).ok());
+    Some((dir_matcher(matchers, dir)?, zone))
+}
+
+// a placeholder for a file that was still being written. once the file settles it gets
+// its real stat, fingerprint, probe results and recorded_at and goes online; returns
+// whether it did
+fn finish_recording(db: &DbState, clip: &Clip, meta: &Metadata, ffprobe_path: &str, dating: Option<(&Matcher, Option<Tz>)>) -> bool {
+    let size = meta.len() as i64;
+    let mtime = mtime_secs(meta).unwrap_or_default();
+    if !stability::is_settled(Path::new(&clip.path), meta) {
+        // keep the size current so the placeholder shows it growing
+        if clip.file_size != size {
+            let _ = db.update_clip_file_stat(&clip.id, size, mtime);
+        }
+        return false;
+    }
+    eprintln!("recording finished: {}", clip.path);
+    refresh_changed_clip(db, clip, size, mtime, ffprobe_path);
+    // the placeholder was dated without probing the container
+    if let Some((matcher, zone)) = dating.filter(|_| clip.recorded_at_source != "manual") {
+        let path = Path::new(&clip.path);
+        let stem = path.file_stem().and_then(|n| n.to_str()).unwrap_or_default();
+        if let Some((ts, source)) = resolve_recorded_at(matcher, stem, path, Some(meta), zone, Some(ffprobe_path)) {
+            let _ = db.update_recorded_at(&clip.id, ts, source);
+        }
+    }
+    db.set_clip_status(std::slice::from_ref(&clip.id), "online").is_ok()
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipReason {
+    // filename doesn't match the dir's pattern profile
+    PatternMismatch,
+    // include/exclude globs filtered it out
+    Excluded,
+    // couldn't stat/read it, or no usable timestamp anywhere
+    Unreadable,
+    // already picked up through another (overlapping) watch dir,
+    // or merged away as a duplicate of another clip
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+// what a scan did, by path. offline/removed only list clips that changed state this time
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub relinked: Vec<String>,
+    pub offline: Vec<String>,
+    // gone from disk, kept as `missing` until purged
+    pub removed: Vec<String>,
+    // still being written, added as placeholders until they settle
+    pub recording: Vec<String>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    pub phase: &'static str,
+    // watch dirs, so the frontend progress bar has something to fill
+    pub total: usize,
+    pub done: usize,
+    pub dir: String,
+    pub file: Option<String>,
+    pub files_seen: usize,
+}
+
+// per watch dir settings resolved once before walking it
+struct DirScan<'a> {
+    dir: &'a WatchDir,
+    matcher: &'a Matcher,
+    zone: Option<Tz>,
+}
+
+// state carried across every file of one scan
+struct ScanState<'a> {
+    db: &'a DbState,
+    ffprobe_path: &'a str,
+    now: i64,
+    // files that vanished since the last scan. a new file with the same
+    // fingerprint is the same clip after a move/rename
+    orphan_ids: HashSet<String>,
+    orphans_by_fp: HashMap<String, String>,
+    seen_paths: HashSet<String>,
+    ignored_paths: HashSet<String>,
+    report: ScanReport,
+}
 
-    for dir in &watch_dirs {
-        let dir_path = Path::new(dir);
+impl ScanState<'_> {
+    fn skip(&mut self, path: &Path, reason: SkipReason) {
+        self.report.skipped.push(SkippedFile { path: path.to_string_lossy().to_string(), reason });
+    }
+
+    fn scan_file(&mut self, scan: &DirScan, filter: &DirFilter, path: &Path) {
+        if !media::is_video(path) { return; }
+        if !filter.allows_file(path) { return self.skip(path, SkipReason::Excluded); }
+
+        let Some(filename) = path.file_name().and_then(|n| n.to_str()) else {
+            return self.skip(path, SkipReason::Unreadable);
+        };
+        let path_str = path.to_string_lossy().to_string();
+        if !self.seen_paths.insert(path_str.clone()) || self.ignored_paths.contains(&path_str) {
+            return self.skip(path, SkipReason::Duplicate);
+        }
+
+        // known rows stay regardless of pattern, e.g. ingested clips with camera names
+        if self.db.clip_exists_by_path(&path_str).unwrap_or(false) { return; }
+
+        let stem = path.file_stem().and_then(|n| n.to_str()).unwrap_or_default();
+        if !scan.matcher.is_match(stem) { return self.skip(path, SkipReason::PatternMismatch); }
+
+        let Ok(meta) = std::fs::metadata(path) else {
+            return self.skip(path, SkipReason::Unreadable);
+        };
+        // half-written files would fingerprint, probe and thumbnail wrong, hold them back
+        // as placeholders until finish_recording picks them up
+        let settled = stability::is_settled(path, &meta);
+
+        let fingerprint = if settled { fingerprint::fingerprint(path).ok() } else { None };
+        if let Some(id) = fingerprint.as_ref().and_then(|fp| self.orphans_by_fp.remove(fp)) {
+            eprintln!("relinking moved clip {} -> {}", id, path_str);
+            match self.db.relink_clip(&id, &path_str, filename, Some(&scan.dir.id), &scan.dir.label) {
+                Ok(()) => {
+                    self.orphan_ids.remove(&id);
+                    self.report.relinked.push(path_str);
+                }
+                Err(e) => eprintln!("relink clip: {}", e),
+            }
+            return;
+        }
+
+        let ffprobe = settled.then_some(self.ffprobe_path);
+        let Some((recorded_at, recorded_at_source)) =
+            resolve_recorded_at(scan.matcher, stem, path, Some(&meta), scan.zone, ffprobe)
+        else {
+            return self.skip(path, SkipReason::Unreadable);
+        };
+
+        let clip = Clip {
+            id: uuid::Uuid::new_v4().to_string(),
+            filename: filename.to_string(),
+            path: path_str.clone(),
+            dir_source: scan.dir.label.clone(),
+            watch_dir_id: Some(scan.dir.id.clone()),
+            recorded_at,
+            recorded_at_source: recorded_at_source.to_string(),
+            file_size: meta.len() as i64,
+            file_mtime: mtime_secs(&meta),
+            fingerprint,
+            duration_secs: None,
+            width: None,
+            height: None,
+            thumb_path: None,
+            thumb_detail_path: None,
+            cover_time: None,
+            description: String::new(),
+            tags: vec![],
+            starred: false,
+            status: if settled { "online" } else { "recording" }.to_string(),
+            created_at: self.now,
+            updated_at: self.now,
+        };
+
+        match self.db.insert_clip(&clip) {
+            Ok(()) => {
+                if let Some(sc) = sidecar::read(path, &sidecar::mode(self.db)) {
+                    if let Err(e) = sidecar::apply(self.db, &clip, &sc) {
+                        eprintln!("sidecar {}: {}", path_str, e);
+                    }
+                }
+                if settled {
+                    self.report.added.push(path_str);
+                } else {
+                    self.report.recording.push(path_str);
+                }
+            }
+            Err(e) => eprintln!("insert clip: {}", e),
+        }
+    }
+}
+
+fn load_matchers(db: &DbState) -> Result<HashMap<String, Matcher>, String> {
+    let mut matchers = HashMap::new();
+    for profile in db.get_pattern_profiles()? {
+        match Matcher::new(&profile) {
+            Ok(m) => { matchers.insert(profile.id, m); }
+            Err(e) => eprintln!("pattern {}", e),
+        }
+    }
+    Ok(matchers)
+}
+
+fn dir_matcher<'a>(matchers: &'a HashMap<String, Matcher>, dir: &WatchDir) -> Option<&'a Matcher> {
+    dir.profile_id.as_ref().and_then(|id| matchers.get(id))
+        .or_else(|| matchers.get(patterns::DEFAULT_PROFILE))
+}
+
+pub fn scan_dirs(db: &DbState, ffprobe_path: &str, on_progress: &dyn Fn(&ScanProgress)) -> Result<ScanReport, String> {
+    db.link_unowned_clips()?;
+    let watch_dirs = db.get_watch_dirs()?;
+    let matchers = load_matchers(db)?;
+
+    // a missing root means an unplugged drive or unreachable share, not deleted files
+    let root_online: HashMap<&str, bool> = watch_dirs.iter()
+        .map(|d| (d.id.as_str(), Path::new(&d.path).is_dir()))
+        .collect();
+
+    let mut state = ScanState {
+        db,
+        ffprobe_path,
+        now: chrono::Utc::now().timestamp(),
+        orphan_ids: HashSet::new(),
+        orphans_by_fp: HashMap::new(),
+        seen_paths: HashSet::new(),
+        ignored_paths: db.get_ignored_paths()?,
+        report: ScanReport::default(),
+    };
+    // already-missing clips stay orphans (they can still be relinked) but aren't reported again
+    let mut newly_missing: HashMap<String, String> = HashMap::new();
+    let mut offline_ids = vec![];
+    let mut online_ids = vec![];
+    for clip in db.get_all_clips()? {
+        // a clip outside every watch dir stays missing even if the file is still there
+        let meta = std::fs::metadata(&clip.path).ok().filter(|_| clip.watch_dir_id.is_some());
+        let Some(meta) = meta else {
+            let root_missing = clip.watch_dir_id.as_deref()
+                .and_then(|id| root_online.get(id))
+                .is_some_and(|online| !online);
+            if root_missing {
+                if clip.status != "offline" {
+                    state.report.offline.push(clip.path);
+                }
+                offline_ids.push(clip.id);
+                continue;
+            }
+            if let Some(fp) = clip.fingerprint {
+                state.orphans_by_fp.insert(fp, clip.id.clone());
+            }
+            if clip.status != "missing" {
+                newly_missing.insert(clip.id.clone(), clip.path);
+            }
+            state.orphan_ids.insert(clip.id);
+            continue;
+        };
+
+        if clip.status == "recording" {
+            let dir = clip.watch_dir_id.as_deref().and_then(|id| watch_dirs.iter().find(|d| d.id == id));
+            if finish_recording(db, &clip, &meta, ffprobe_path, dating(&matchers, dir)) {
+                state.report.updated.push(clip.path);
+            } else {
+                state.report.recording.push(clip.path);
+            }
+            continue;
+        }
+        if clip.status != "online" {
+            online_ids.push(clip.id.clone());
+        }
+
+        let size = meta.len() as i64;
+        let mtime = mtime_secs(&meta);
+        match (clip.file_mtime, mtime) {
+            (Some(old), Some(new)) if old != new || clip.file_size != size => {
+                eprintln!("clip changed on disk: {}", clip.path);
+                refresh_changed_clip(db, &clip, size, new, ffprobe_path);
+                state.report.updated.push(clip.path);
+                continue;
+            }
+            // rows from before mtimes were tracked
+            (None, Some(new)) => { let _ = db.update_clip_file_stat(&clip.id, size, new); }
+            _ => {}
+        }
+        if clip.fingerprint.is_none() {
+            // backfill rows from before fingerprints existed
+            if let Ok(fp) = fingerprint::fingerprint(Path::new(&clip.path)) {
+                let _ = db.update_clip_fingerprint(&clip.id, &fp);
+            }
+        }
+    }
+    db.set_clip_status(&offline_ids, "offline")?;
+    db.set_clip_status(&online_ids, "online")?;
+
+    let enabled: Vec<&WatchDir> = watch_dirs.iter().filter(|d| d.enabled).collect();
+    let total = enabled.len();
+    let mut files_seen = 0;
+
+    for (i, wd) in enabled.into_iter().enumerate() {
+        on_progress(&ScanProgress {
+            phase: "scanning", total, done: i, dir: wd.path.clone(), file: None, files_seen,
+        });
+
+        let dir_path = Path::new(&wd.path);
         if !dir_path.exists() { continue; }
 
-        // walk 2 levels deep (picks up immediate files + one subfolder like Captures/)
-        for entry in WalkDir::new(dir_path).min_depth(1).max_depth(2) {
+        let Some(matcher) = dir_matcher(&matchers, wd) else { continue };
+        let zone = wd.timezone.as_deref().and_then(|name| tz::parse_zone(name).ok());
+        let filter = match DirFilter::new(wd) {
+            Ok(f) => f,
+            Err(e) => { eprintln!("watch dir {}: {}", wd.path, e); continue; }
+        };
+        let scan = DirScan { dir: wd, matcher, zone };
+
+        // prune excluded / too-deep folders instead of walking into them
+        let walker = WalkDir::new(dir_path).min_depth(1).into_iter()
+            .filter_entry(|e| !e.file_type().is_dir() || filter.allows_dir(e.path()));
+
+        for entry in walker {
             let entry = match entry {
                 Ok(e) => e,
-                Err(_) => continue,
+                Err(e) => {
+                    if let Some(p) = e.path() {
+                        state.skip(p, SkipReason::Unreadable);
+                    }
+                    continue;
+                }
             };
             if !entry.file_type().is_file() { continue; }
 
-            let path = entry.path();
-            let filename = match path.file_name().and_then(|n| n.to_str()) {
-                Some(f) => f,
-                None => continue,
-            };
+            files_seen += 1;
+            // one event per file floods the frontend on big libraries
+            if files_seen % 10 == 0 {
+                on_progress(&ScanProgress {
+                    phase: "scanning",
+                    total,
+                    done: i,
+                    dir: wd.path.clone(),
+                    file: Some(entry.path().to_string_lossy().to_string()),
+                    files_seen,
+                });
+            }
+            state.scan_file(&scan, &filter, entry.path());
+        }
+    }
+
+    // whatever didn't turn up under a new path is missing. keep the row and its
+    // metadata around until the user purges it or the file comes back
+    let orphans: Vec<String> = state.orphan_ids.into_iter().collect();
+    db.set_clip_status(&orphans, "missing")?;
+    let mut report = state.report;
+    report.removed = orphans.iter().filter_map(|id| newly_missing.remove(id)).collect();
 
-            if !re.is_match(filename) { continue; }
+    on_progress(&ScanProgress {
+        phase: "complete", total, done: total, dir: String::new(), file: None, files_seen,
+    });
+    eprintln!(
+        "scan: {} added, {} updated, {} relinked, {} offline, {} removed, {} recording, {} skipped",
+        report.added.len(), report.updated.len(), report.relinked.len(),
+        report.offline.len(), report.removed.len(), report.recording.len(), report.skipped.len(),
+    );
+    Ok(report)
+}
 
-            let path_str = path.to_string_lossy().to_string();
+// a filesystem change reported by the watcher, already narrowed to one path
+#[derive(Debug, Clone)]
+pub enum PathEvent {
+    // created or written to
+    Upsert(PathBuf),
+    Remove(PathBuf),
+    Rename(PathBuf, PathBuf),
+}
 
-            if seen_paths.contains(&path_str) { continue; }
-            seen_paths.insert(path_str.clone());
+// what handling a path event did to the library, with the clip as it is now
+#[derive(Debug, Clone)]
+pub enum ClipChange {
+    Added(Clip),
+    Changed(Clip),
+    // the row stays around as missing/offline, same as after a full scan
+    Removed(Clip),
+}
 
-            if db.clip_exists_by_path(&path_str).unwrap_or(false) { continue; }
+impl ClipChange {
+    pub fn event(&self) -> &'static str {
+        match self {
+            ClipChange::Added(_) => "clip-added",
+            ClipChange::Changed(_) => "clip-changed",
+            ClipChange::Removed(_) => "clip-removed",
+        }
+    }
 
-            let recorded_at = match parse_timestamp(filename) {
-                Some(ts) => ts,
-                None => continue,
-            };
+    pub fn clip(&self) -> &Clip {
+        match self {
+            ClipChange::Added(c) | ClipChange::Changed(c) | ClipChange::Removed(c) => c,
+        }
+    }
+}
 
-            let file_size = entry.metadata().map(|m| m.len() as i64).unwrap_or(0);
-
-            // derive source label from parent dir name
-            let source = path.parent()
-                .and_then(|p| p.file_name())
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_lowercase();
-
-            let clip = Clip {
-                id: uuid::Uuid::new_v4().to_string(),
-                filename: filename.to_string(),
-                path: path_str,
-                dir_source: source,
-                recorded_at,
-                file_size,
-                duration_secs: None,
-                width: None,
-                height: None,
-                thumb_path: None,
-                description: String::new(),
-                tags: vec![],
-                starred: false,
-                created_at: now,
-                updated_at: now,
-            };
+// handle watcher events one path at a time instead of walking every watch dir.
+// same rules as scan_dirs: first matching watch dir wins, moves are relinked by fingerprint
+pub fn scan_paths(db: &DbState, ffprobe_path: &str, events: &[PathEvent]) -> Result<Vec<ClipChange>, String> {
+    let library = Library {
+        dirs: db.get_watch_dirs()?.into_iter()
+            .filter(|d| d.enabled)
+            .filter_map(|d| DirFilter::new(&d).ok().map(|f| (d, f)))
+            .collect(),
+        matchers: load_matchers(db)?,
+    };
 
-            if let Err(e) = db.insert_clip(&clip) {
-                eprintln!("insert clip: {}", e);
+    let mut state = ScanState {
+        db,
+        ffprobe_path,
+        now: chrono::Utc::now().timestamp(),
+        orphan_ids: HashSet::new(),
+        orphans_by_fp: db.get_missing_fingerprints()?,
+        seen_paths: HashSet::new(),
+        ignored_paths: db.get_ignored_paths()?,
+        report: ScanReport::default(),
+    };
+    let mut changes = vec![];
+    for event in events {
+        match event {
+            PathEvent::Rename(from, to) => {
+                if let Some(clip) = db.get_clip_by_path(&from.to_string_lossy())? {
+                    changes.extend(rename_clip(&mut state, &library, clip, to)?);
+                    continue;
+                }
+                // a renamed folder carries every clip below it along
+                for id in db.get_clip_ids_under(&from.to_string_lossy())? {
+                    let Some(clip) = db.get_clip(&id)? else { continue };
+                    let Ok(rel) = Path::new(&clip.path).strip_prefix(from) else { continue };
+                    let dest = to.join(rel);
+                    changes.extend(rename_clip(&mut state, &library, clip, &dest)?);
+                }
+                changes.extend(upsert_tree(&mut state, &library, to)?);
             }
+            PathEvent::Remove(path) => {
+                if path.exists() { continue; }
+                if let Some(clip) = db.get_clip_by_path(&path.to_string_lossy())? {
+                    changes.extend(mark_gone(&mut state, clip)?);
+                    continue;
+                }
+                for id in db.get_clip_ids_under(&path.to_string_lossy())? {
+                    if let Some(clip) = db.get_clip(&id)? {
+                        changes.extend(mark_gone(&mut state, clip)?);
+                    }
+                }
+            }
+            PathEvent::Upsert(path) => changes.extend(upsert_tree(&mut state, &library, path)?),
         }
     }
+    Ok(changes)
+}
 
-    // orphan detection: remove clips whose files no longer exist
-    let all = db.get_all_clips()?;
-    let orphans: Vec<String> = all.iter()
-        .filter(|c| !Path::new(&c.path).exists())
-        .map(|c| c.id.clone())
-        .collect();
-    if !orphans.is_empty() {
-        eprintln!("removing {} orphaned clips", orphans.len());
-        db.delete_clips(&orphans)?;
+fn rename_clip(state: &mut ScanState, library: &Library, clip: Clip, to: &Path) -> Result<Option<ClipChange>, String> {
+    let db = state.db;
+    let filename = to.file_name().and_then(|n| n.to_str());
+    match (library.owner(to), filename) {
+        (Some((wd, _)), Some(filename)) if media::is_video(to) => {
+            let to_str = to.to_string_lossy();
+            eprintln!("relinking renamed clip {} -> {}", clip.id, to_str);
+            db.relink_clip(&clip.id, &to_str, filename, Some(&wd.id), &wd.label)?;
+            Ok(db.get_clip(&clip.id)?.map(ClipChange::Changed))
+        }
+        // moved out of every watch dir, or renamed to something that isn't a video
+        _ => mark_gone(state, clip),
+    }
+}
+
+// a file, or every file below a folder that was created or moved in
+fn upsert_tree(state: &mut ScanState, library: &Library, path: &Path) -> Result<Vec<ClipChange>, String> {
+    if !path.is_dir() {
+        return Ok(upsert_path(state, library, path)?.into_iter().collect());
+    }
+    let mut changes = vec![];
+    for entry in WalkDir::new(path).min_depth(1).into_iter().flatten() {
+        if entry.file_type().is_file() && media::is_video(entry.path()) {
+            changes.extend(upsert_path(state, library, entry.path())?);
+        }
+    }
+    Ok(changes)
+}
+
+// enabled watch dirs and their rules, for placing single paths
+struct Library {
+    dirs: Vec<(WatchDir, DirFilter)>,
+    matchers: HashMap<String, Matcher>,
+}
+
+impl Library {
+    // overlapping watch dirs: the first one wins, same as the walk order in scan_dirs
+    fn owner(&self, path: &Path) -> Option<(&WatchDir, &DirFilter)> {
+        self.dirs.iter().find(|(_, f)| f.allows_file(path)).map(|(d, f)| (d, f))
+    }
+}
+
+fn upsert_path(state: &mut ScanState, library: &Library, path: &Path) -> Result<Option<ClipChange>, String> {
+    let db = state.db;
+    let path_str = path.to_string_lossy().to_string();
+
+    if let Some(clip) = db.get_clip_by_path(&path_str)? {
+        let Ok(meta) = std::fs::metadata(path) else {
+            return mark_gone(state, clip);
+        };
+        if clip.status == "recording" {
+            let dir = clip.watch_dir_id.as_deref()
+                .and_then(|id| library.dirs.iter().map(|(d, _)| d).find(|d| d.id == id));
+            let finished = finish_recording(db, &clip, &meta, state.ffprobe_path, dating(&library.matchers, dir));
+            // a still-growing placeholder only changes size
+            if !finished && clip.file_size == meta.len() as i64 {
+                return Ok(None);
+            }
+            return Ok(db.get_clip(&clip.id)?.map(ClipChange::Changed));
+        }
+        let size = meta.len() as i64;
+        let changed = match (clip.file_mtime, mtime_secs(&meta)) {
+            (Some(old), Some(new)) if old != new || clip.file_size != size => {
+                eprintln!("clip changed on disk: {}", clip.path);
+                refresh_changed_clip(db, &clip, size, new, state.ffprobe_path);
+                true
+            }
+            _ => false,
+        };
+        if clip.status != "online" {
+            db.set_clip_status(std::slice::from_ref(&clip.id), "online")?;
+        } else if !changed {
+            return Ok(None);
+        }
+        return Ok(db.get_clip(&clip.id)?.map(ClipChange::Changed));
+    }
+
+    if !path.is_file() { return Ok(None); }
+    let Some((wd, filter)) = library.owner(path) else { return Ok(None) };
+    let Some(matcher) = dir_matcher(&library.matchers, wd) else { return Ok(None) };
+    let zone = wd.timezone.as_deref().and_then(|name| tz::parse_zone(name).ok());
+
+    let added = state.report.added.len() + state.report.recording.len();
+    let relinked = state.report.relinked.len();
+    state.scan_file(&DirScan { dir: wd, matcher, zone }, filter, path);
+
+    let clip = || db.get_clip_by_path(&path_str);
+    if state.report.added.len() + state.report.recording.len() > added {
+        return Ok(clip()?.map(ClipChange::Added));
+    }
+    if state.report.relinked.len() > relinked {
+        return Ok(clip()?.map(ClipChange::Changed));
+    }
+    Ok(None)
+}
+
+// the file is gone: offline when its whole watch root went away, missing otherwise.
+// missing clips stay relinkable by fingerprint for the rest of the batch
+fn mark_gone(state: &mut ScanState, mut clip: Clip) -> Result<Option<ClipChange>, String> {
+    let root_online = match &clip.watch_dir_id {
+        Some(id) => state.db.get_watch_dirs()?.iter()
+            .find(|d| &d.id == id)
+            .is_none_or(|d| Path::new(&d.path).is_dir()),
+        None => true,
+    };
+    let status = if root_online { "missing" } else { "offline" };
+    if clip.status == status { return Ok(None); }
+
+    state.db.set_clip_status(std::slice::from_ref(&clip.id), status)?;
+    if let Some(fp) = &clip.fingerprint {
+        if status == "missing" {
+            state.orphans_by_fp.insert(fp.clone(), clip.id.clone());
+        }
+    }
+    clip.status = status.to_string();
+    Ok(Some(ClipChange::Removed(clip)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a finished file: written a while ago, so it doesn't look like a recording in progress
+    fn write_settled(path: &Path, bytes: &[u8]) {
+        std::fs::write(path, bytes).unwrap();
+        let past = std::time::SystemTime::now() - stability::SETTLE * 2;
+        std::fs::File::options().write(true).open(path).unwrap().set_modified(past).unwrap();
+    }
+
+    #[test]
+    fn test_scan_report() {
+        let root = std::env::temp_dir().join(format!("boxy-scan-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        write_settled(&root.join("2024-01-28 18-40-28.mp4"), b"clip one");
+        write_settled(&root.join("holiday.mp4"), b"clip two");
+        std::fs::write(root.join("notes.txt"), b"not media").unwrap();
+
+        let db = DbState::in_memory().unwrap();
+        db.init().unwrap();
+        add_watch_dir(&db, &root.to_string_lossy(), None, None).unwrap();
+
+        let report = scan_dirs(&db, "ffprobe-not-installed", &|_| {}).unwrap();
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].path.ends_with("holiday.mp4"));
+        assert!(matches!(report.skipped[0].reason, SkipReason::PatternMismatch));
+
+        // unchanged tree: nothing to report besides the same skip
+        let report = scan_dirs(&db, "ffprobe-not-installed", &|_| {}).unwrap();
+        assert!(report.added.is_empty() && report.removed.is_empty());
+
+        std::fs::remove_file(root.join("2024-01-28 18-40-28.mp4")).unwrap();
+        let report = scan_dirs(&db, "ffprobe-not-installed", &|_| {}).unwrap();
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(db.get_all_clips().unwrap()[0].status, "missing");
+
+        // only reported on the scan where it went missing
+        let report = scan_dirs(&db, "ffprobe-not-installed", &|_| {}).unwrap();
+        assert!(report.removed.is_empty());
+
+        // still being written: a placeholder until it settles
+        let live = root.join("2024-01-28 19-00-00.mp4");
+        std::fs::write(&live, b"partial").unwrap();
+        let report = scan_dirs(&db, "ffprobe-not-installed", &|_| {}).unwrap();
+        assert_eq!(report.recording.len(), 1);
+        assert!(report.added.is_empty());
+        // nothing is read from a file that's still growing
+        let clip = db.get_clip_by_path(&live.to_string_lossy()).unwrap().unwrap();
+        assert_eq!(clip.fingerprint, None);
+        write_settled(&live, b"partial, now finished");
+        let report = scan_dirs(&db, "ffprobe-not-installed", &|_| {}).unwrap();
+        assert_eq!(report.updated.len(), 1);
+        let clip = db.get_clip_by_path(&live.to_string_lossy()).unwrap().unwrap();
+        assert_eq!((clip.status.as_str(), clip.file_size), ("online", 21));
+        assert!(clip.fingerprint.is_some());
+        assert_eq!(clip.recorded_at_source, "filename");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_scan_paths() {
+        let root = std::env::temp_dir().join(format!("boxy-paths-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let db = DbState::in_memory().unwrap();
+        db.init().unwrap();
+        add_watch_dir(&db, &root.to_string_lossy(), None, None).unwrap();
+        let ffprobe = "ffprobe-not-installed";
+
+        let first = root.join("2024-01-28 18-40-28.mp4");
+        write_settled(&first, b"clip one");
+        let changes = scan_paths(&db, ffprobe, &[PathEvent::Upsert(first.clone())]).unwrap();
+        assert!(matches!(changes.as_slice(), [ClipChange::Added(_)]));
+        let id = changes[0].clip().id.clone();
+
+        // same stat again: nothing happened
+        assert!(scan_paths(&db, ffprobe, &[PathEvent::Upsert(first.clone())]).unwrap().is_empty());
+
+        // rename keeps the row
+        let renamed = root.join("2024-01-28 18-40-29.mp4");
+        std::fs::rename(&first, &renamed).unwrap();
+        let changes = scan_paths(&db, ffprobe, &[PathEvent::Rename(first.clone(), renamed.clone())]).unwrap();
+        assert!(matches!(&changes[..], [ClipChange::Changed(c)] if c.id == id && c.path == renamed.to_string_lossy()));
+
+        // remove + create elsewhere in one batch relinks by fingerprint
+        let moved = root.join("2024-01-28 18-40-30.mp4");
+        std::fs::rename(&renamed, &moved).unwrap();
+        let changes = scan_paths(&db, ffprobe, &[PathEvent::Remove(renamed.clone()), PathEvent::Upsert(moved.clone())]).unwrap();
+        assert_eq!(changes.iter().map(|c| c.event()).collect::<Vec<_>>(), ["clip-removed", "clip-changed"]);
+        assert_eq!(changes[1].clip().id, id);
+        assert_eq!(changes[1].clip().status, "online");
+
+        std::fs::remove_file(&moved).unwrap();
+        let changes = scan_paths(&db, ffprobe, &[PathEvent::Remove(moved.clone())]).unwrap();
+        assert!(matches!(&changes[..], [ClipChange::Removed(c)] if c.status == "missing"));
+        assert_eq!(db.get_all_clips().unwrap().len(), 1);
+
+        // folders: a new one is walked, a renamed one carries its clips, a removed one loses them
+        let sub = root.join("day1");
+        std::fs::create_dir_all(&sub).unwrap();
+        write_settled(&sub.join("2024-01-29 10-00-00.mp4"), b"clip two");
+        let changes = scan_paths(&db, ffprobe, &[PathEvent::Upsert(sub.clone())]).unwrap();
+        assert!(matches!(changes.as_slice(), [ClipChange::Added(_)]));
+        let id = changes[0].clip().id.clone();
+
+        let renamed = root.join("day one");
+        std::fs::rename(&sub, &renamed).unwrap();
+        let changes = scan_paths(&db, ffprobe, &[PathEvent::Rename(sub.clone(), renamed.clone())]).unwrap();
+        assert!(matches!(&changes[..], [ClipChange::Changed(c)] if c.id == id && c.path.starts_with(&*renamed.to_string_lossy())));
+
+        std::fs::remove_dir_all(&renamed).unwrap();
+        let changes = scan_paths(&db, ffprobe, &[PathEvent::Remove(renamed.clone())]).unwrap();
+        assert!(matches!(&changes[..], [ClipChange::Removed(c)] if c.id == id && c.status == "missing"));
+
+        std::fs::remove_dir_all(&root).unwrap();
     }
 
-    db.get_all_clips()
+    #[test]
+    fn test_removed_watch_dir() {
+        let root = std::env::temp_dir().join(format!("boxy-unwatch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        write_settled(&root.join("2024-01-28 18-40-28.mp4"), b"clip one");
+        let db = DbState::in_memory().unwrap();
+        db.init().unwrap();
+        let dir = add_watch_dir(&db, &root.to_string_lossy(), None, None).unwrap();
+        scan_dirs(&db, "ffprobe-not-installed", &|_| {}).unwrap();
+
+        // the file is still there, but nothing watches it any more
+        db.delete_watch_dir(&dir.id).unwrap();
+        scan_dirs(&db, "ffprobe-not-installed", &|_| {}).unwrap();
+        let clips = db.get_all_clips().unwrap();
+        assert_eq!((clips.len(), clips[0].status.as_str()), (1, "missing"));
+
+        // adding the folder back picks the same row up again
+        let dir = add_watch_dir(&db, &root.to_string_lossy(), None, None).unwrap();
+        scan_dirs(&db, "ffprobe-not-installed", &|_| {}).unwrap();
+        let clips = db.get_all_clips().unwrap();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].status, "online");
+        assert_eq!(clips[0].watch_dir_id.as_deref(), Some(dir.id.as_str()));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -35,6 +35,37 @@ pub fn vec_to_bytes(v: &[f32]) -> Vec<u8> {
     v.iter().flat_map(|f| f.to_le_bytes()).collect()
 }
 
+// simple embedding (bag-of-words cosine similarity placeholder)
+pub fn simple_embed(text: &str) -> Vec<f32> {
+    let mut vec = vec![0.0f32; 384];
+    let lower = text.to_lowercase();
+    for word in lower.split_whitespace() {
+        let mut h = 0u64;
+        for b in word.bytes() {
+            h = h.wrapping_mul(31).wrapping_add(b as u64);
+        }
+        let idx = (h % 384) as usize;
+        vec[idx] += 1.0;
+
+        if word.len() >= 2 {
+            for pair in word.as_bytes().windows(2) {
+                let h2 = (pair[0] as u64).wrapping_mul(31).wrapping_add(pair[1] as u64);
+                let idx2 = (h2 % 384) as usize;
+                vec[idx2] += 0.5;
+            }
+        }
+    }
+
+    let norm: f32 = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for v in vec.iter_mut() {
+            *v /= norm;
+        }
+    }
+
+    vec
+}
+
 // brute force search over all embeddings
 pub fn search(db: &DbState, query_vec: &[f32], limit: usize) -> Result<Vec<SearchResult>, String> {
     let embeddings = db.get_all_embeddings()?;
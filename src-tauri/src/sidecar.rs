@@ -0,0 +1,359 @@
+use crate::db::{Clip, DbState};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// off: db only. json: `clip.mp4.boxy.json`. xmp: `clip.mp4.xmp` for other DAM tools
+pub const MODES: &[&str] = &["off", "json", "xmp"];
+const XMP_NS: &str = "https://github.com/visualstudioblyat/boxy/ns/1.0/";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Marker {
+    pub time: f64,
+    #[serde(default)]
+    pub label: String,
+}
+
+// what travels with the file. tags and collections by name, ids are per-database
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Sidecar {
+    pub tags: Vec<String>,
+    pub starred: bool,
+    pub description: String,
+    // not stored in the db yet, carried over from whatever wrote the file
+    pub markers: Vec<Marker>,
+    pub collections: Vec<String>,
+}
+
+pub fn json_path(clip_path: &Path) -> PathBuf {
+    with_suffix(clip_path, ".boxy.json")
+}
+
+pub fn xmp_path(clip_path: &Path) -> PathBuf {
+    with_suffix(clip_path, ".xmp")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(suffix);
+    PathBuf::from(s)
+}
+
+pub fn mode(db: &DbState) -> String {
+    db.get_meta("sidecar_mode").ok().flatten().unwrap_or_else(|| "off".to_string())
+}
+
+pub fn set_mode(db: &DbState, mode: &str) -> Result<(), String> {
+    if !MODES.contains(&mode) {
+        return Err(format!("unknown sidecar mode: {}", mode));
+    }
+    db.set_meta("sidecar_mode", mode)
+}
+
+// when both exist, the one the current mode writes wins (the other is stale); with
+// sidecars off, whichever was written last
+pub fn read(clip_path: &Path, mode: &str) -> Option<Sidecar> {
+    let mtime = |p: PathBuf| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    let json_first = match mode {
+        "json" => true,
+        "xmp" => false,
+        _ => mtime(json_path(clip_path)) >= mtime(xmp_path(clip_path)),
+    };
+    if json_first {
+        read_json(clip_path).or_else(|| read_xmp(clip_path))
+    } else {
+        read_xmp(clip_path).or_else(|| read_json(clip_path))
+    }
+}
+
+fn read_json(clip_path: &Path) -> Option<Sidecar> {
+    let text = std::fs::read_to_string(json_path(clip_path)).ok()?;
+    serde_json::from_str(&text)
+        .map_err(|e| eprintln!("sidecar {}: {}", clip_path.display(), e))
+        .ok()
+}
+
+fn read_xmp(clip_path: &Path) -> Option<Sidecar> {
+    std::fs::read_to_string(xmp_path(clip_path)).ok().map(|text| from_xmp(&text))
+}
+
+pub fn write(clip_path: &Path, mode: &str, sidecar: &Sidecar) -> Result<(), String> {
+    let (path, text) = match mode {
+        "json" => (json_path(clip_path), serde_json::to_string_pretty(sidecar).map_err(|e| e.to_string())?),
+        "xmp" => (xmp_path(clip_path), to_xmp(sidecar)),
+        _ => return Ok(()),
+    };
+    // temp + rename so a crash never leaves half a sidecar
+    let tmp = with_suffix(&path, ".tmp");
+    std::fs::write(&tmp, text).map_err(|e| format!("write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+fn build(db: &DbState, mode: &str, clip: &Clip, tag_names: &HashMap<String, String>) -> Result<Sidecar, String> {
+    Ok(Sidecar {
+        tags: clip.tags.iter().filter_map(|id| tag_names.get(id).cloned()).collect(),
+        starred: clip.starred,
+        description: clip.description.clone(),
+        markers: read(Path::new(&clip.path), mode).map(|sc| sc.markers).unwrap_or_default(),
+        collections: db.get_clip_collection_names(&clip.id)?,
+    })
+}
+
+// rewrite sidecars for these clips after an edit. failures are logged, the db edit stands
+pub fn sync(db: &DbState, clip_ids: &[String]) {
+    let mode = mode(db);
+    if mode == "off" || clip_ids.is_empty() { return; }
+    // edits touch a clip or a selection, don't walk the whole library for them
+    let clips: Vec<Clip> = clip_ids.iter()
+        .filter_map(|id| db.get_clip(id).ok().flatten())
+        .collect();
+    if let Err(e) = write_clips(db, &mode, &clips) {
+        eprintln!("sidecar sync: {}", e);
+    }
+}
+
+// write a sidecar for every online clip, returns how many were written
+pub fn export_all(db: &DbState) -> Result<usize, String> {
+    let mode = mode(db);
+    if mode == "off" {
+        return Err("sidecars are turned off".into());
+    }
+    write_clips(db, &mode, &db.get_all_clips()?)
+}
+
+fn write_clips(db: &DbState, mode: &str, clips: &[Clip]) -> Result<usize, String> {
+    let tag_names: HashMap<String, String> = db.get_all_tags()?
+        .into_iter()
+        .map(|t| (t.id, t.name))
+        .collect();
+    let mut written = 0;
+    for clip in clips.iter().filter(|c| c.status == "online") {
+        match build(db, mode, clip, &tag_names).and_then(|sc| write(Path::new(&clip.path), mode, &sc)) {
+            Ok(()) => written += 1,
+            Err(e) => eprintln!("sidecar {}: {}", clip.path, e),
+        }
+    }
+    Ok(written)
+}
+
+// merge a sidecar into a clip: tags and collections are added (created by name when
+// new), a star is kept, and the description only fills an empty one
+pub fn apply(db: &DbState, clip: &Clip, sidecar: &Sidecar) -> Result<(), String> {
+    let mut tags: HashMap<String, String> = db.get_all_tags()?
+        .into_iter()
+        .map(|t| (t.name.to_lowercase(), t.id))
+        .collect();
+    for name in sidecar.tags.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        let id = match tags.get(&name.to_lowercase()) {
+            Some(id) => id.clone(),
+            None => {
+                let tag = db.create_tag(&uuid::Uuid::new_v4().to_string(), name, "#6366f1")?;
+                tags.insert(name.to_lowercase(), tag.id.clone());
+                tag.id
+            }
+        };
+        db.add_clip_tag(&clip.id, &id)?;
+    }
+
+    let mut collections: HashMap<String, String> = db.get_all_collections()?
+        .into_iter()
+        .map(|c| (c.name.to_lowercase(), c.id))
+        .collect();
+    for name in sidecar.collections.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        let id = match collections.get(&name.to_lowercase()) {
+            Some(id) => id.clone(),
+            None => {
+                let col = db.create_collection(&uuid::Uuid::new_v4().to_string(), name, "#6366f1")?;
+                collections.insert(name.to_lowercase(), col.id.clone());
+                col.id
+            }
+        };
+        db.add_clips_to_collection(&id, std::slice::from_ref(&clip.id))?;
+    }
+
+    if sidecar.starred && !clip.starred {
+        db.toggle_star(&clip.id, true)?;
+    }
+    if clip.description.trim().is_empty() && !sidecar.description.trim().is_empty() {
+        db.update_description(&clip.id, sidecar.description.trim())?;
+    }
+    Ok(())
+}
+
+// pull every clip's sidecar into the db, returns how many were found
+pub fn import_all(db: &DbState) -> Result<usize, String> {
+    let mode = mode(db);
+    let mut found = 0;
+    for clip in db.get_all_clips()?.iter().filter(|c| c.status == "online") {
+        if let Some(sc) = read(Path::new(&clip.path), &mode) {
+            apply(db, clip, &sc)?;
+            found += 1;
+        }
+    }
+    Ok(found)
+}
+
+// -- xmp --
+
+fn esc(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn unesc(s: &str) -> String {
+    s.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+fn xmp_list(tag: &str, kind: &str, items: &[String]) -> String {
+    if items.is_empty() { return String::new(); }
+    let lis: String = items.iter().map(|i| format!("     <rdf:li>{}</rdf:li>\n", esc(i))).collect();
+    format!("   <{tag}>\n    <rdf:{kind}>\n{lis}    </rdf:{kind}>\n   </{tag}>\n")
+}
+
+fn to_xmp(sc: &Sidecar) -> String {
+    let mut body = xmp_list("dc:subject", "Bag", &sc.tags);
+    if !sc.description.is_empty() {
+        body.push_str(&format!(
+            "   <dc:description>\n    <rdf:Alt>\n     <rdf:li xml:lang=\"x-default\">{}</rdf:li>\n    </rdf:Alt>\n   </dc:description>\n",
+            esc(&sc.description)
+        ));
+    }
+    body.push_str(&xmp_list("boxy:collections", "Bag", &sc.collections));
+    if !sc.markers.is_empty() {
+        let lis: String = sc.markers.iter()
+            .map(|m| format!("     <rdf:li boxy:time=\"{}\">{}</rdf:li>\n", m.time, esc(&m.label)))
+            .collect();
+        body.push_str(&format!("   <boxy:markers>\n    <rdf:Seq>\n{lis}    </rdf:Seq>\n   </boxy:markers>\n"));
+    }
+    let rating = if sc.starred { "\n    xmp:Rating=\"5\"" } else { "" };
+
+    format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>
+<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">
+ <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">
+  <rdf:Description rdf:about=\"\"
+    xmlns:dc=\"http://purl.org/dc/elements/1.1/\"
+    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"
+    xmlns:boxy=\"{XMP_NS}\"{rating}>
+{body}  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end=\"w\"?>
+"
+    )
+}
+
+// not a general xml parser, just enough for what we and common DAM tools write
+fn from_xmp(text: &str) -> Sidecar {
+    let li = Regex::new(r#"(?s)<rdf:li([^>]*)>(.*?)</rdf:li>"#).unwrap();
+    let block = |tag: &str| -> Vec<(String, String)> {
+        let re = Regex::new(&format!(r"(?s)<{tag}>(.*?)</{tag}>", tag = regex::escape(tag))).unwrap();
+        re.captures(text)
+            .map(|c| li.captures_iter(&c[1]).map(|l| (l[1].to_string(), unesc(l[2].trim()))).collect())
+            .unwrap_or_default()
+    };
+    let time = Regex::new(r#"boxy:time="([0-9.]+)""#).unwrap();
+    let rating = Regex::new(r#"xmp:Rating(?:="|>)(-?\d+)"#).unwrap();
+
+    Sidecar {
+        tags: block("dc:subject").into_iter().map(|(_, v)| v).collect(),
+        // any positive rating from another tool counts as a pick
+        starred: rating.captures(text).and_then(|c| c[1].parse::<i32>().ok()).is_some_and(|r| r > 0),
+        description: block("dc:description").into_iter().next().map(|(_, v)| v).unwrap_or_default(),
+        markers: block("boxy:markers").into_iter()
+            .filter_map(|(attrs, label)| {
+                let t = time.captures(&attrs)?[1].parse().ok()?;
+                Some(Marker { time: t, label })
+            })
+            .collect(),
+        collections: block("boxy:collections").into_iter().map(|(_, v)| v).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Sidecar {
+        Sidecar {
+            tags: vec!["clutch".into(), "R&D <test>".into()],
+            starred: true,
+            description: "1v4 on \"B\" site".into(),
+            markers: vec![Marker { time: 12.5, label: "ace".into() }],
+            collections: vec!["Best of 2024".into()],
+        }
+    }
+
+    #[test]
+    fn test_xmp_roundtrip() {
+        assert_eq!(from_xmp(&to_xmp(&sample())), sample());
+        assert_eq!(from_xmp(&to_xmp(&Sidecar::default())), Sidecar::default());
+    }
+
+    #[test]
+    fn test_apply_and_sync() {
+        let dir = std::env::temp_dir().join(format!("boxy-sidecar-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let clip_path = dir.join("clip.mp4");
+        std::fs::write(&clip_path, b"video").unwrap();
+        write(&clip_path, "json", &sample()).unwrap();
+        assert_eq!(read(&clip_path, "off"), Some(sample()));
+
+        let db = DbState::in_memory().unwrap();
+        db.init().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let clip = Clip {
+            id: "c1".into(),
+            filename: "clip.mp4".into(),
+            path: clip_path.to_string_lossy().to_string(),
+            dir_source: "test".into(),
+            watch_dir_id: None,
+            recorded_at: now,
+            recorded_at_source: "filename".into(),
+            file_size: 5,
+            file_mtime: None,
+            fingerprint: None,
+            duration_secs: None,
+            width: None,
+            height: None,
+            thumb_path: None,
+            thumb_detail_path: None,
+            cover_time: None,
+            description: String::new(),
+            tags: vec![],
+            starred: false,
+            status: "online".into(),
+            created_at: now,
+            updated_at: now,
+        };
+        db.insert_clip(&clip).unwrap();
+        apply(&db, &clip, &read(&clip_path, "off").unwrap()).unwrap();
+
+        let stored = &db.get_all_clips().unwrap()[0];
+        assert_eq!(stored.tags.len(), 2);
+        assert!(stored.starred);
+        assert_eq!(stored.description, "1v4 on \"B\" site");
+        assert_eq!(db.get_clip_collection_names("c1").unwrap(), vec!["Best of 2024"]);
+
+        // edits write back, markers survive the rewrite
+        db.set_meta("sidecar_mode", "json").unwrap();
+        db.update_description("c1", "new text").unwrap();
+        sync(&db, &["c1".to_string()]);
+        let written = read(&clip_path, "json").unwrap();
+        assert_eq!(written.description, "new text");
+        assert_eq!(written.markers, sample().markers);
+
+        // an older xmp next to it only wins when the library writes xmp
+        let xmp = Sidecar { description: "from xmp".into(), ..Sidecar::default() };
+        write(&clip_path, "xmp", &xmp).unwrap();
+        let past = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+        std::fs::File::options().write(true).open(xmp_path(&clip_path)).unwrap().set_modified(past).unwrap();
+        assert_eq!(read(&clip_path, "json").unwrap().description, "new text");
+        assert_eq!(read(&clip_path, "xmp").unwrap().description, "from xmp");
+        assert_eq!(read(&clip_path, "off").unwrap().description, "new text");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+// a file has to sit untouched this long before it counts as finished. OBS flushes
+// every second or two while recording, so anything quieter than this has stopped
+pub const SETTLE: Duration = Duration::from_secs(5);
+
+// another process still has the file open for writing. on windows recorders hold a
+// share lock until they finalize the file; elsewhere there's no cheap equivalent and
+// the settle time has to do
+#[cfg(windows)]
+pub fn is_locked(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+
+    match std::fs::OpenOptions::new().read(true).share_mode(0).open(path) {
+        Ok(_) => false,
+        Err(e) => e.raw_os_error() == Some(ERROR_SHARING_VIOLATION),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn is_locked(_path: &Path) -> bool {
+    false
+}
+
+// written to within the settle window
+pub fn is_growing(meta: &Metadata, now: SystemTime) -> bool {
+    meta.modified().ok()
+        .and_then(|m| now.duration_since(m).ok())
+        .is_some_and(|age| age < SETTLE)
+}
+
+// safe to probe, thumbnail and hash
+pub fn is_settled(path: &Path, meta: &Metadata) -> bool {
+    !is_growing(meta, SystemTime::now()) && !is_locked(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_growing() {
+        let path = std::env::temp_dir().join(format!("boxy-settle-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"partial").unwrap();
+        let meta = std::fs::metadata(&path).unwrap();
+
+        assert!(is_growing(&meta, SystemTime::now()));
+        assert!(!is_settled(&path, &meta));
+        assert!(!is_growing(&meta, SystemTime::now() + SETTLE));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use crate::db::DbState;
+use crate::thumbs;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+
+// set while settings changed and the library hasn't caught up, so a restart resumes
+const PENDING_KEY: &str = "thumb_job_pending";
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbJobStatus {
+    pub running: bool,
+    pub done: usize,
+    pub total: usize,
+    // clips whose thumbnails don't match the current settings
+    pub stale: usize,
+}
+
+struct Worker {
+    app: AppHandle,
+    db: DbState,
+    ffmpeg_path: String,
+    thumbs_dir: PathBuf,
+    status: Arc<Mutex<ThumbJobStatus>>,
+}
+
+// regenerates thumbnails made with old settings, one clip at a time on a background
+// thread. kicking it mid-run starts over with the settings as they are now
+#[derive(Clone)]
+pub struct ThumbJob {
+    tx: Sender<()>,
+    db: DbState,
+    status: Arc<Mutex<ThumbJobStatus>>,
+}
+
+impl ThumbJob {
+    pub fn spawn(app: AppHandle, db: DbState, ffmpeg_path: String, thumbs_dir: PathBuf) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let status = Arc::new(Mutex::new(ThumbJobStatus::default()));
+        let worker = Worker { app, db: db.clone(), ffmpeg_path, thumbs_dir, status: status.clone() };
+        std::thread::spawn(move || worker.run(rx));
+
+        let job = ThumbJob { tx, db, status };
+        if job.db.get_meta(PENDING_KEY).ok().flatten().is_some() {
+            job.start();
+        }
+        job
+    }
+
+    pub fn start(&self) {
+        let _ = self.db.set_meta(PENDING_KEY, "1");
+        let _ = self.tx.send(());
+    }
+
+    pub fn status(&self) -> ThumbJobStatus {
+        let mut status = self.status.lock().clone();
+        let sig = thumbs::thumb_settings(&self.db).signature();
+        status.stale = self.db.get_stale_thumbs(&sig).map(|c| c.len()).unwrap_or_default();
+        status
+    }
+}
+
+impl Worker {
+    fn run(self, rx: Receiver<()>) {
+        while rx.recv().is_ok() {
+            // a pass restarts whenever another kick arrives while it runs
+            while self.pass(&rx) {}
+            self.status.lock().running = false;
+        }
+    }
+
+    // true if it was interrupted by a new kick
+    fn pass(&self, rx: &Receiver<()>) -> bool {
+        while rx.try_recv().is_ok() {}
+        let sig = thumbs::thumb_settings(&self.db).signature();
+        let stale = match self.db.get_stale_thumbs(&sig) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("thumb job: {}", e); return false; }
+        };
+        *self.status.lock() = ThumbJobStatus { running: true, done: 0, total: stale.len(), stale: 0 };
+        eprintln!("thumb job: {} clips out of date", stale.len());
+
+        let mut done = 0;
+        let mut interrupted = false;
+        for (clip_id, path) in &stale {
+            if rx.try_recv().is_ok() {
+                interrupted = true;
+                break;
+            }
+            match thumbs::gen_thumb(&self.db, clip_id, path, &self.thumbs_dir, &self.ffmpeg_path) {
+                Ok(thumb) => { let _ = self.app.emit("thumb-ready", thumb); }
+                Err(e) => eprintln!("thumb job {}: {}", path, e),
+            }
+            done += 1;
+            self.status.lock().done = done;
+            let _ = self.app.emit("scan-progress", serde_json::json!({
+                "total": stale.len(), "done": done, "phase": "thumbnails"
+            }));
+        }
+        // clears the progress bar; an interrupted pass starts a fresh one right away
+        let _ = self.app.emit("scan-progress", serde_json::json!({
+            "total": stale.len(), "done": done, "phase": "complete"
+        }));
+        if !interrupted {
+            let _ = self.db.delete_meta(PENDING_KEY);
+        }
+        interrupted
+    }
+}
@@ -1,55 +1,588 @@
 use crate::db::DbState;
 use crate::ffmpeg;
-use std::path::PathBuf;
+use crate::media;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
-// generate thumbnail for a single clip using ffmpeg
+// fixed grabs the frame at 2s; smart samples the clip and skips black frames,
+// fades and loading screens
+pub const THUMB_MODES: &[&str] = &["smart", "fixed"];
+pub const THUMB_FORMATS: &[&str] = &["jpeg", "webp", "avif"];
+// frames sampled across the clip in smart mode
+const CANDIDATES: usize = 6;
+// the thumbnail filter keeps the most representative of this many frames after each seek,
+// so a candidate doesn't land on a transition
+const WINDOW_FRAMES: u32 = 30;
+// tiny grayscale frame the scoring runs on
+const SAMPLE_W: usize = 64;
+const SAMPLE_H: usize = 36;
+// mean luma below this is a black/fade frame, above it a white flash
+const DARK: f64 = 0.06;
+const BRIGHT: f64 = 0.94;
+
+// output settings for both variants. the grid one is what thumb_path points at, the
+// detail one is for the side panel and larger layouts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbSettings {
+    // jpeg, webp or avif
+    pub format: String,
+    // 1-100, mapped onto each encoder's own scale
+    pub quality: u32,
+    pub grid_width: u32,
+    pub detail_width: u32,
+}
+
+impl Default for ThumbSettings {
+    fn default() -> Self {
+        ThumbSettings { format: "jpeg".into(), quality: 85, grid_width: 480, detail_width: 1280 }
+    }
+}
+
+impl ThumbSettings {
+    // stamped on each clip's thumbnails; a clip whose stamp differs is out of date
+    pub fn signature(&self) -> String {
+        format!("{}-q{}-{}-{}", self.format, self.quality, self.grid_width, self.detail_width)
+    }
+
+    fn ext(&self) -> &'static str {
+        match self.format.as_str() {
+            "webp" => "webp",
+            "avif" => "avif",
+            _ => "jpg",
+        }
+    }
+
+    fn codec_args(&self) -> Vec<String> {
+        let q = self.quality.clamp(1, 100);
+        match self.format.as_str() {
+            "webp" => vec!["-c:v".into(), "libwebp".into(), "-quality".into(), q.to_string()],
+            // crf 0 is lossless, 63 the worst
+            "avif" => vec![
+                "-c:v".into(), "libaom-av1".into(), "-still-picture".into(), "1".into(),
+                "-crf".into(), (63 - q * 63 / 100).to_string(),
+            ],
+            // mjpeg qscale runs 2 (best) to 31. most of the useful range sits near the top,
+            // so the default 85 lands on 3 (what thumbnails always used) and 100 on 2
+            _ => vec!["-q:v".into(), jpeg_qscale(q).to_string()],
+        }
+    }
+}
+
+fn jpeg_qscale(quality: u32) -> u32 {
+    if quality >= 85 {
+        2 + (100 - quality) / 15
+    } else {
+        3 + (85 - quality) * 28 / 84
+    }
+}
+
+pub fn thumb_settings(db: &DbState) -> ThumbSettings {
+    db.get_meta("thumb_settings").ok().flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_thumb_settings(db: &DbState, settings: &ThumbSettings) -> Result<(), String> {
+    if !THUMB_FORMATS.contains(&settings.format.as_str()) {
+        return Err(format!("unknown thumbnail format: {}", settings.format));
+    }
+    if !(1..=100).contains(&settings.quality) {
+        return Err("quality must be between 1 and 100".into());
+    }
+    for width in [settings.grid_width, settings.detail_width] {
+        if !(64..=3840).contains(&width) {
+            return Err(format!("thumbnail width {} is out of range (64-3840)", width));
+        }
+    }
+    db.set_meta("thumb_settings", &serde_json::to_string(settings).map_err(|e| e.to_string())?)
+}
+
+// a clip's freshly written thumbnails, also the thumb-ready payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Thumbnail {
+    pub clip_id: String,
+    pub thumb_path: String,
+    pub thumb_detail_path: String,
+    pub updated_at: i64,
+}
+
+// fixed unless turned on: smart seeks and decodes several candidate frames per clip
+pub fn thumb_mode(db: &DbState) -> String {
+    db.get_meta("thumb_mode").ok().flatten().unwrap_or_else(|| "fixed".to_string())
+}
+
+pub fn set_thumb_mode(db: &DbState, mode: &str) -> Result<(), String> {
+    if !THUMB_MODES.contains(&mode) {
+        return Err(format!("unknown thumbnail mode: {}", mode));
+    }
+    db.set_meta("thumb_mode", mode)
+}
+
+// generate thumbnail for a single clip using the global mode
 pub fn gen_thumb(
     db: &DbState,
     clip_id: &str,
     video_path: &str,
     thumbs_dir: &PathBuf,
     ffmpeg_path: &str,
-) -> Result<String, String> {
-    let thumb_filename = format!("{}.jpg", clip_id);
-    let thumb_path = thumbs_dir.join(&thumb_filename);
-    let thumb_str = thumb_path.to_string_lossy().to_string();
+) -> Result<Thumbnail, String> {
+    gen_thumb_as(db, clip_id, video_path, thumbs_dir, ffmpeg_path, &thumb_mode(db))
+}
+
+pub fn gen_thumb_as(
+    db: &DbState,
+    clip_id: &str,
+    video_path: &str,
+    thumbs_dir: &Path,
+    ffmpeg_path: &str,
+    mode: &str,
+) -> Result<Thumbnail, String> {
+    if !media::is_video(Path::new(video_path)) {
+        return Err(format!("unsupported media type: {}", video_path));
+    }
+
+    let settings = thumb_settings(db);
+    let out = Variants {
+        grid: thumbs_dir.join(format!("{}.{}", clip_id, settings.ext())).to_string_lossy().to_string(),
+        detail: thumbs_dir.join(format!("{}-detail.{}", clip_id, settings.ext())).to_string_lossy().to_string(),
+    };
+
+    // probe runs before thumbnails, so the duration is usually known by now
+    let clip = db.get_clip(clip_id).ok().flatten();
+    let duration = clip.as_ref().and_then(|c| c.duration_secs);
+
+    let extracted = if let Some(t) = clip.as_ref().and_then(|c| c.cover_time) {
+        // the user's pick wins over any mode, frame exact
+        extract_frame(ffmpeg_path, video_path, t, None, &settings, &out)?
+    } else {
+        let picked = if mode == "smart" { best_frame(ffmpeg_path, video_path, duration) } else { None };
+        match picked {
+            Some(t) => extract_frame(ffmpeg_path, video_path, t, Some(WINDOW_FRAMES), &settings, &out)?,
+            // extract frame at 2s, or at 0s if 2s fails (short clips)
+            None => extract_frame(ffmpeg_path, video_path, 2.0, None, &settings, &out)?
+                || extract_frame(ffmpeg_path, video_path, 0.0, None, &settings, &out)?,
+        }
+    };
+    if !extracted {
+        return Err("ffmpeg failed to extract frame".into());
+    }
+
+    // a format change leaves the old files behind under another extension
+    if let Some(clip) = &clip {
+        for old in [&clip.thumb_path, &clip.thumb_detail_path].into_iter().flatten() {
+            if *old != out.grid && *old != out.detail {
+                let _ = std::fs::remove_file(old);
+            }
+        }
+    }
+
+    db.update_clip_thumb(clip_id, &out.grid, Some(&out.detail), Some(&settings.signature()))?;
+    Ok(Thumbnail {
+        clip_id: clip_id.to_string(),
+        thumb_path: out.grid,
+        thumb_detail_path: out.detail,
+        updated_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+// pin the thumbnail to `time` seconds into the clip and regenerate it. None clears the
+// pick and goes back to the global mode. the previous pick is restored if the frame
+// can't be extracted
+pub fn set_cover(
+    db: &DbState,
+    clip_id: &str,
+    thumbs_dir: &Path,
+    ffmpeg_path: &str,
+    time: Option<f64>,
+) -> Result<Thumbnail, String> {
+    let clip = db.get_clip(clip_id)?.ok_or_else(|| format!("clip not found: {}", clip_id))?;
+    if let Some(t) = time {
+        if !t.is_finite() || t < 0.0 || clip.duration_secs.is_some_and(|d| t > d) {
+            return Err(format!("cover time {:.2}s is outside the clip", t));
+        }
+    }
+    db.set_clip_cover_time(clip_id, time)?;
+    gen_thumb_as(db, clip_id, &clip.path, thumbs_dir, ffmpeg_path, &thumb_mode(db)).inspect_err(|_| {
+        let _ = db.set_clip_cover_time(clip_id, clip.cover_time);
+    })
+}
+
+struct Variants {
+    grid: String,
+    detail: String,
+}
+
+// both variants from one decode. never upscales past the source width
+fn extract_frame(
+    ffmpeg_path: &str,
+    video_path: &str,
+    at: f64,
+    window: Option<u32>,
+    settings: &ThumbSettings,
+    out: &Variants,
+) -> Result<bool, String> {
+    let pick = window.map(|n| format!("thumbnail={},", n)).unwrap_or_default();
+    let graph = format!(
+        "[0:v]{}split=2[a][b];[a]scale='min({},iw)':-2[grid];[b]scale='min({},iw)':-2[detail]",
+        pick, settings.grid_width, settings.detail_width,
+    );
+    let codec = settings.codec_args();
+    let output = ffmpeg::cmd(ffmpeg_path)
+        .args(["-y", "-ss", &format!("{:.3}", at), "-i", video_path, "-filter_complex", &graph])
+        .args(["-map", "[grid]", "-frames:v", "1"]).args(&codec).arg(&out.grid)
+        .args(["-map", "[detail]", "-frames:v", "1"]).args(&codec).arg(&out.detail)
+        .output()
+        .map_err(|e| format!("ffmpeg: {}", e))?;
+    Ok(output.status.success())
+}
+
+// seek points for smart mode. the first second is skipped (intros, fade-ins) and so
+// is the tail (end cards). unknown duration tries a few early offsets
+fn candidate_times(duration: Option<f64>) -> Vec<f64> {
+    match duration {
+        Some(d) if d > 2.0 => {
+            let start = 1.0_f64.min(d * 0.05);
+            let span = d * 0.9 - start;
+            (0..CANDIDATES).map(|i| start + span * (i as f64 + 0.5) / CANDIDATES as f64).collect()
+        }
+        Some(d) if d > 0.0 => vec![d / 2.0],
+        _ => vec![1.0, 2.0, 4.0, 8.0, 15.0],
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FrameStats {
+    // mean luma, 0..1
+    brightness: f64,
+    // shannon entropy of the luma histogram, 0..8 bits
+    entropy: f64,
+}
+
+fn frame_stats(gray: &[u8]) -> FrameStats {
+    let mut hist = [0usize; 256];
+    for &px in gray {
+        hist[px as usize] += 1;
+    }
+    let n = gray.len().max(1) as f64;
+    let entropy = hist.iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| { let p = c as f64 / n; -p * p.log2() })
+        .sum();
+    let brightness = gray.iter().map(|&px| px as f64).sum::<f64>() / n / 255.0;
+    FrameStats { brightness, entropy }
+}
+
+// mean absolute luma difference, 0..1. how much a frame stands out from the clip's
+// typical look; a loading screen that sits there for seconds scores low
+fn difference(a: &[u8], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len()).max(1) as f64;
+    a.iter().zip(b).map(|(&x, &y)| (x as f64 - y).abs()).sum::<f64>() / n / 255.0
+}
+
+// higher is better. black and blown-out frames are out regardless of detail
+fn score(stats: FrameStats, distinct: f64) -> f64 {
+    if stats.brightness < DARK || stats.brightness > BRIGHT {
+        return 0.0;
+    }
+    // mid-tones read best at thumbnail size
+    let exposure = 1.0 - (stats.brightness - 0.45).abs() * 2.0;
+    // a quarter of full scale average difference already counts as a different scene
+    0.6 * (stats.entropy / 8.0) + 0.25 * (distinct * 4.0).min(1.0) + 0.15 * exposure.max(0.0)
+}
 
-    // extract frame at 2s
+// decode a small grayscale sample at `at`, after the same thumbnail filter the final
+// extraction uses so the scored frame is the one that gets saved
+fn sample_frame(ffmpeg_path: &str, video_path: &str, at: f64) -> Option<Vec<u8>> {
+    let vf = format!("thumbnail={},scale={}:{},format=gray", WINDOW_FRAMES, SAMPLE_W, SAMPLE_H);
     let output = ffmpeg::cmd(ffmpeg_path)
         .args([
-            "-y",
-            "-ss", "2",
+            "-ss", &format!("{:.3}", at),
             "-i", video_path,
             "-frames:v", "1",
-            "-vf", "scale=320:-1",
-            "-q:v", "3",
-            &thumb_str,
+            "-vf", &vf,
+            "-f", "rawvideo",
+            "-",
         ])
         .output()
-        .map_err(|e| format!("ffmpeg: {}", e))?;
+        .ok()?;
+    let frame = output.stdout;
+    (output.status.success() && frame.len() == SAMPLE_W * SAMPLE_H).then_some(frame)
+}
 
-    if !output.status.success() {
-        // try at 0s if 2s fails (short clips)
-        let output2 = ffmpeg::cmd(ffmpeg_path)
-            .args([
-                "-y",
-                "-ss", "0",
-                "-i", video_path,
-                "-frames:v", "1",
-                "-vf", "scale=320:-1",
-                "-q:v", "3",
-                &thumb_str,
-            ])
-            .output()
-            .map_err(|e| format!("ffmpeg retry: {}", e))?;
-
-        if !output2.status.success() {
-            return Err("ffmpeg failed to extract frame".into());
+fn sample_candidates(ffmpeg_path: &str, video_path: &str, duration: Option<f64>) -> Vec<(f64, Vec<u8>)> {
+    candidate_times(duration).into_iter()
+        .filter_map(|t| sample_frame(ffmpeg_path, video_path, t).map(|f| (t, f)))
+        .collect()
+}
+
+// seek time of the best-looking candidate, None if nothing decoded or every
+// candidate is black
+fn best_frame(ffmpeg_path: &str, video_path: &str, duration: Option<f64>) -> Option<f64> {
+    rank(&sample_candidates(ffmpeg_path, video_path, duration)).first().copied()
+}
+
+// candidate times, best first. black and blown-out frames are dropped
+fn rank(frames: &[(f64, Vec<u8>)]) -> Vec<f64> {
+    let Some((_, first)) = frames.first() else { return vec![] };
+    let mut mean = vec![0.0; first.len()];
+    for (_, f) in frames {
+        for (m, &px) in mean.iter_mut().zip(f) {
+            *m += px as f64 / frames.len() as f64;
         }
     }
+    let mut scored: Vec<(f64, f64)> = frames.iter()
+        .map(|(t, f)| (*t, score(frame_stats(f), difference(f, &mean))))
+        .filter(|(_, s)| *s > 0.0)
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.into_iter().map(|(t, _)| t).collect()
+}
+
+// hover scrubbing sprite: one sheet of evenly spaced frames per clip
+const SPRITE_COLUMNS: u32 = 10;
+const SPRITE_ROWS: u32 = 10;
+const SPRITE_TILE_WIDTH: u32 = 160;
+// typical keyframe spacing of recordings; tiles closer together than this would
+// repeat the same keyframe if only keyframes were decoded
+const SPRITE_KEYFRAME_SECS: f64 = 3.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpriteTile {
+    pub start: f64,
+    pub end: f64,
+    pub x: u32,
+    pub y: u32,
+}
+
+// written as json next to the sheet, along with a webvtt copy of the same cues
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpriteIndex {
+    pub image_path: String,
+    pub vtt_path: String,
+    pub columns: u32,
+    pub rows: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    // seconds between frames
+    pub interval: f64,
+    pub tiles: Vec<SpriteTile>,
+}
+
+fn sprite_paths(thumbs_dir: &Path, clip_id: &str) -> [PathBuf; 3] {
+    ["jpg", "vtt", "json"].map(|ext| thumbs_dir.join(format!("{}-sprite.{}", clip_id, ext)))
+}
+
+// sprite sheet and preview loop, regenerated on demand
+pub fn remove_cached(thumbs_dir: &Path, clip_id: &str) {
+    for path in sprite_paths(thumbs_dir, clip_id) {
+        let _ = std::fs::remove_file(path);
+    }
+    let _ = std::fs::remove_file(preview_path(thumbs_dir, clip_id));
+}
+
+fn sprite_index(image: &Path, vtt: &Path, duration: f64, tile_height: u32) -> SpriteIndex {
+    let count = SPRITE_COLUMNS * SPRITE_ROWS;
+    let interval = duration / count as f64;
+    let tiles = (0..count)
+        .map(|i| SpriteTile {
+            start: i as f64 * interval,
+            end: (i + 1) as f64 * interval,
+            x: (i % SPRITE_COLUMNS) * SPRITE_TILE_WIDTH,
+            y: (i / SPRITE_COLUMNS) * tile_height,
+        })
+        .collect();
+    SpriteIndex {
+        image_path: image.to_string_lossy().to_string(),
+        vtt_path: vtt.to_string_lossy().to_string(),
+        columns: SPRITE_COLUMNS,
+        rows: SPRITE_ROWS,
+        tile_width: SPRITE_TILE_WIDTH,
+        tile_height,
+        interval,
+        tiles,
+    }
+}
+
+fn vtt_time(secs: f64) -> String {
+    let ms = (secs * 1000.0).round() as u64;
+    format!("{:02}:{:02}:{:02}.{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+}
+
+// media fragment cues, the format players use for seek previews
+fn sprite_vtt(index: &SpriteIndex) -> String {
+    let image = Path::new(&index.image_path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut out = String::from("WEBVTT\n");
+    for tile in &index.tiles {
+        out.push_str(&format!(
+            "\n{} --> {}\n{}#xywh={},{},{},{}\n",
+            vtt_time(tile.start), vtt_time(tile.end), image, tile.x, tile.y, index.tile_width, index.tile_height,
+        ));
+    }
+    out
+}
+
+// cached sheet for the clip, generated on first request. a sheet older than the
+// video file is rebuilt
+pub fn gen_sprite(db: &DbState, clip_id: &str, thumbs_dir: &Path, ffmpeg_path: &str) -> Result<SpriteIndex, String> {
+    let clip = db.get_clip(clip_id)?.ok_or_else(|| format!("clip not found: {}", clip_id))?;
+    let duration = clip.duration_secs.filter(|d| *d > 0.0)
+        .ok_or_else(|| format!("no duration for {}, probe it first", clip.filename))?;
+    let [image, vtt, json] = sprite_paths(thumbs_dir, clip_id);
+
+    let video_mtime = std::fs::metadata(&clip.path).and_then(|m| m.modified()).ok();
+    let json_mtime = std::fs::metadata(&json).and_then(|m| m.modified()).ok();
+    if json_mtime.is_some() && json_mtime >= video_mtime && image.exists() {
+        if let Some(index) = std::fs::read_to_string(&json).ok().and_then(|t| serde_json::from_str(&t).ok()) {
+            return Ok(index);
+        }
+    }
+
+    // keep the source aspect, even height for the scaler
+    let tile_height = match (clip.width, clip.height) {
+        (Some(w), Some(h)) if w > 0 && h > 0 => ((SPRITE_TILE_WIDTH as f64 * h as f64 / w as f64 / 2.0).round() as u32 * 2).max(2),
+        _ => SPRITE_TILE_WIDTH * 9 / 16,
+    };
+    let index = sprite_index(&image, &vtt, duration, tile_height);
+
+    let vf = format!(
+        "fps=1/{:.4},scale={}:{},tile={}x{}",
+        index.interval, SPRITE_TILE_WIDTH, tile_height, SPRITE_COLUMNS, SPRITE_ROWS,
+    );
+    let mut cmd = ffmpeg::cmd(ffmpeg_path);
+    cmd.arg("-y");
+    // keyframes only on long clips: decoding every frame of a long recording is what
+    // made the frontend version slow, and tiles that far apart don't need exact frames.
+    // short clips decode everything so neighbouring tiles don't all show one keyframe
+    if index.interval >= SPRITE_KEYFRAME_SECS {
+        cmd.args(["-skip_frame", "nokey"]);
+    }
+    // write next to the real files and rename, json last: it's what marks the sprite complete
+    let image_part = part_path(&image);
+    let output = cmd
+        .args(["-i", &clip.path, "-an", "-vf", &vf, "-frames:v", "1", "-q:v", "4", "-f", "image2"])
+        .arg(&image_part)
+        .output()
+        .map_err(|e| format!("ffmpeg: {}", e))?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&image_part);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("sprite failed: {}", stderr.chars().take(500).collect::<String>()));
+    }
+    std::fs::rename(&image_part, &image).map_err(|e| format!("rename: {}", e))?;
+
+    write_part(&vtt, sprite_vtt(&index).as_bytes())?;
+    write_part(&json, serde_json::to_string(&index).map_err(|e| e.to_string())?.as_bytes())?;
+    Ok(index)
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+fn write_part(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let part = part_path(path);
+    std::fs::write(&part, contents).map_err(|e| e.to_string())?;
+    std::fs::rename(&part, path).map_err(|e| format!("rename: {}", e))
+}
+
+// animated grid previews: a few short highlights stitched into a silent loop
+pub const HOVER_PREVIEWS: &[&str] = &["filmstrip", "loop"];
+const PREVIEW_SEGMENTS: usize = 3;
+const PREVIEW_SEGMENT_SECS: f64 = 1.0;
+const PREVIEW_WIDTH: u32 = 320;
+
+// what hovering a grid card shows: the scrub sprite or the preview loop
+pub fn hover_preview(db: &DbState) -> String {
+    db.get_meta("hover_preview").ok().flatten().unwrap_or_else(|| "filmstrip".to_string())
+}
+
+pub fn set_hover_preview(db: &DbState, mode: &str) -> Result<(), String> {
+    if !HOVER_PREVIEWS.contains(&mode) {
+        return Err(format!("unknown hover preview: {}", mode));
+    }
+    db.set_meta("hover_preview", mode)
+}
+
+fn preview_path(thumbs_dir: &Path, clip_id: &str) -> PathBuf {
+    thumbs_dir.join(format!("{}-preview.mp4", clip_id))
+}
 
-    db.update_clip_thumb(clip_id, &thumb_str)?;
-    Ok(thumb_str)
+// segment starts in playback order. the best-scoring candidates when the clip is long
+// enough to have a choice, otherwise just the opening seconds
+fn highlight_starts(ranked: &[f64], duration: f64) -> Vec<f64> {
+    let total = PREVIEW_SEGMENTS as f64 * PREVIEW_SEGMENT_SECS;
+    if duration <= total * 2.0 || ranked.is_empty() {
+        return vec![0.0];
+    }
+    let mut starts: Vec<f64> = ranked.iter()
+        .take(PREVIEW_SEGMENTS)
+        .map(|t| t.min(duration - PREVIEW_SEGMENT_SECS).max(0.0))
+        .collect();
+    starts.sort_by(|a, b| a.total_cmp(b));
+    starts
+}
+
+// cached loop for the clip, generated on first request and rebuilt when the video
+// is newer. returns the mp4 path
+pub fn gen_preview(db: &DbState, clip_id: &str, thumbs_dir: &Path, ffmpeg_path: &str) -> Result<String, String> {
+    let clip = db.get_clip(clip_id)?.ok_or_else(|| format!("clip not found: {}", clip_id))?;
+    let duration = clip.duration_secs.filter(|d| *d > 0.0)
+        .ok_or_else(|| format!("no duration for {}, probe it first", clip.filename))?;
+    let out = preview_path(thumbs_dir, clip_id);
+    let out_str = out.to_string_lossy().to_string();
+
+    let video_mtime = std::fs::metadata(&clip.path).and_then(|m| m.modified()).ok();
+    let preview_mtime = std::fs::metadata(&out).and_then(|m| m.modified()).ok();
+    if preview_mtime.is_some() && preview_mtime >= video_mtime {
+        return Ok(out_str);
+    }
+
+    let ranked = if duration > PREVIEW_SEGMENTS as f64 * PREVIEW_SEGMENT_SECS * 2.0 {
+        rank(&sample_candidates(ffmpeg_path, &clip.path, Some(duration)))
+    } else {
+        vec![]
+    };
+    let starts = highlight_starts(&ranked, duration);
+    // a single segment covers the whole short clip, up to the loop length
+    let seg_len = if starts.len() == 1 {
+        duration.min(PREVIEW_SEGMENTS as f64 * PREVIEW_SEGMENT_SECS)
+    } else {
+        PREVIEW_SEGMENT_SECS
+    };
+
+    // one input per segment so each one fast-seeks instead of decoding from the start
+    let mut cmd = ffmpeg::cmd(ffmpeg_path);
+    cmd.arg("-y");
+    for start in &starts {
+        cmd.args(["-ss", &format!("{:.3}", start), "-t", &format!("{:.3}", seg_len), "-i", &clip.path]);
+    }
+    let mut graph: String = (0..starts.len())
+        .map(|i| format!("[{}:v]scale='min({},iw)':-2,fps=24,setsar=1[v{}];", i, PREVIEW_WIDTH, i))
+        .collect();
+    graph.push_str(&(0..starts.len()).map(|i| format!("[v{}]", i)).collect::<String>());
+    graph.push_str(&format!("concat=n={}:v=1:a=0[out]", starts.len()));
+
+    // encode next to the real file and rename, so a half-written loop is never served
+    let part = format!("{}.part", out_str);
+    let output = cmd
+        .args(["-filter_complex", &graph, "-map", "[out]", "-an"])
+        .args(["-c:v", "libx264", "-preset", "veryfast", "-crf", "30", "-pix_fmt", "yuv420p", "-movflags", "+faststart"])
+        .args(["-f", "mp4", &part])
+        .output()
+        .map_err(|e| format!("ffmpeg: {}", e))?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&part);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("preview failed: {}", stderr.chars().take(500).collect::<String>()));
+    }
+    std::fs::rename(&part, &out).map_err(|e| format!("rename: {}", e))?;
+    Ok(out_str)
 }
 
 // probe video metadata using ffprobe
@@ -93,3 +626,97 @@ pub fn probe_meta(video_path: &str, ffprobe_path: &str) -> Result<(f64, i32, i32
 
     Ok((duration, width, height))
 }
+
+// read format.tags.creation_time (set by most cameras/phones, some recorders)
+pub fn probe_creation_time(video_path: &str, ffprobe_path: &str) -> Option<i64> {
+    let output = ffmpeg::cmd(ffprobe_path)
+        .args([
+            "-v", "quiet",
+            "-print_format", "json",
+            "-show_entries", "format_tags=creation_time",
+            video_path,
+        ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let raw = json["format"]["tags"]["creation_time"].as_str()?;
+    let ts = chrono::DateTime::parse_from_rfc3339(raw).ok()?.timestamp();
+
+    // muxers without a clock write the epoch
+    if ts <= 0 { None } else { Some(ts) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank() {
+        let black = vec![2u8; SAMPLE_W * SAMPLE_H];
+        let flat_grey = vec![110u8; SAMPLE_W * SAMPLE_H];
+        let detailed: Vec<u8> = (0..SAMPLE_W * SAMPLE_H).map(|i| ((i * 37) % 200 + 30) as u8).collect();
+
+        assert_eq!(score(frame_stats(&black), 0.5), 0.0);
+        assert!(frame_stats(&detailed).entropy > frame_stats(&flat_grey).entropy);
+
+        let frames = vec![(1.0, black.clone()), (5.0, flat_grey.clone()), (9.0, detailed), (13.0, flat_grey)];
+        assert_eq!(rank(&frames).first(), Some(&9.0));
+        // the black frame never makes the list
+        assert_eq!(rank(&frames).len(), 3);
+        assert!(rank(&[(1.0, black)]).is_empty());
+        assert!(rank(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_thumb_settings() {
+        let settings = ThumbSettings::default();
+        assert_eq!(settings.codec_args(), vec!["-q:v", "3"]);
+        let best = ThumbSettings { quality: 100, ..settings.clone() };
+        assert_eq!(best.codec_args(), vec!["-q:v", "2"]);
+        let webp = ThumbSettings { format: "webp".into(), ..settings.clone() };
+        assert_eq!(webp.ext(), "webp");
+        assert_ne!(webp.signature(), settings.signature());
+    }
+
+    #[test]
+    fn test_jpeg_qscale() {
+        let scale: Vec<u32> = [1, 50, 84, 85, 90, 100].into_iter().map(jpeg_qscale).collect();
+        assert_eq!(scale, [31, 14, 3, 3, 2, 2]);
+        // never gets worse as quality goes up
+        assert!((1..100).all(|q| jpeg_qscale(q) >= jpeg_qscale(q + 1)));
+    }
+
+    #[test]
+    fn test_sprite_index() {
+        let index = sprite_index(Path::new("/t/c1-sprite.jpg"), Path::new("/t/c1-sprite.vtt"), 200.0, 90);
+        assert_eq!(index.tiles.len(), 100);
+        assert_eq!(index.interval, 2.0);
+        assert_eq!((index.tiles[11].x, index.tiles[11].y), (160, 90));
+        assert_eq!(index.tiles[99].end, 200.0);
+
+        let vtt = sprite_vtt(&index);
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:02.000\nc1-sprite.jpg#xywh=0,0,160,90\n"));
+        assert_eq!(vtt_time(3725.5), "01:02:05.500");
+    }
+
+    #[test]
+    fn test_highlight_starts() {
+        assert_eq!(highlight_starts(&[40.0, 5.0, 59.9, 20.0], 60.0), vec![5.0, 40.0, 59.0]);
+        assert_eq!(highlight_starts(&[2.0], 4.0), vec![0.0]);
+        assert_eq!(highlight_starts(&[], 60.0), vec![0.0]);
+    }
+
+    #[test]
+    fn test_candidate_times() {
+        let times = candidate_times(Some(60.0));
+        assert_eq!(times.len(), CANDIDATES);
+        assert!(times[0] >= 1.0 && times[CANDIDATES - 1] <= 54.0);
+        assert_eq!(candidate_times(Some(1.0)), vec![0.5]);
+        assert!(!candidate_times(None).is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+use chrono::{Duration, Local, LocalResult, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::Tz;
+
+// IANA zone name like "Europe/Berlin"
+pub fn parse_zone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>().map_err(|_| format!("unknown timezone: {}", name))
+}
+
+// interpret a wall-clock time from a filename in `zone` (system zone when None)
+pub fn local_to_utc(naive: NaiveDateTime, zone: Option<Tz>) -> i64 {
+    match zone {
+        Some(tz) => resolve(&tz, naive),
+        None => resolve(&Local, naive),
+    }
+}
+
+// the wall-clock time `ts` showed in `zone`, the inverse of local_to_utc
+pub fn utc_to_local(ts: i64, zone: Option<Tz>) -> Option<NaiveDateTime> {
+    let utc = chrono::DateTime::from_timestamp(ts, 0)?;
+    Some(match zone {
+        Some(tz) => utc.with_timezone(&tz).naive_local(),
+        None => utc.with_timezone(&Local).naive_local(),
+    })
+}
+
+fn resolve<Z: TimeZone>(zone: &Z, naive: NaiveDateTime) -> i64 {
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(dt) => dt.timestamp(),
+        // fall-back: the hour repeats, take the first pass through it
+        LocalResult::Ambiguous(first, _) => first.timestamp(),
+        // spring-forward gap: the wall time never existed, so the clock that wrote it
+        // was still on the old offset. the first valid time past the gap gives the new
+        // offset, and just before the gap (read on the new offset) gives the old one.
+        // no fixed shift, gaps aren't always an hour (Australia/Lord_Howe skips 30 minutes)
+        LocalResult::None => {
+            let wall = naive.and_utc().timestamp();
+            (1..=24 * 60)
+                .find_map(|m| zone.from_local_datetime(&(naive + Duration::minutes(m))).earliest())
+                .and_then(|after| zone.timestamp_opt(wall - after.offset().fix().local_minus_utc() as i64, 0).earliest())
+                .map(|before| wall - before.offset().fix().local_minus_utc() as i64)
+                .unwrap_or(wall)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_local_to_utc_dst() {
+        let ny = parse_zone("America/New_York").ok();
+
+        // plain winter time, UTC-5
+        assert_eq!(local_to_utc(naive("2024-01-28 18:40:28"), ny), naive("2024-01-28 23:40:28").and_utc().timestamp());
+
+        // 2024-11-03 01:30 happens twice, first pass is EDT (UTC-4)
+        assert_eq!(local_to_utc(naive("2024-11-03 01:30:00"), ny), naive("2024-11-03 05:30:00").and_utc().timestamp());
+
+        // 2024-03-10 02:30 doesn't exist, still on EST (UTC-5)
+        assert_eq!(local_to_utc(naive("2024-03-10 02:30:00"), ny), naive("2024-03-10 07:30:00").and_utc().timestamp());
+
+        // half-hour gap: 2024-10-06 02:00 jumps to 02:30, 02:15:20 is still on +10:30
+        let lord_howe = parse_zone("Australia/Lord_Howe").ok();
+        assert_eq!(local_to_utc(naive("2024-10-06 02:15:20"), lord_howe), naive("2024-10-05 15:45:20").and_utc().timestamp());
+        assert_eq!(local_to_utc(naive("2024-10-06 02:30:00"), lord_howe), naive("2024-10-05 15:30:00").and_utc().timestamp());
+    }
+
+    #[test]
+    fn test_utc_to_local() {
+        let berlin = parse_zone("Europe/Berlin").ok();
+        let ts = local_to_utc(naive("2024-07-01 12:00:00"), berlin);
+        assert_eq!(utc_to_local(ts, berlin), Some(naive("2024-07-01 12:00:00")));
+        assert_eq!(utc_to_local(ts, parse_zone("UTC").ok()), Some(naive("2024-07-01 10:00:00")));
+    }
+
+    #[test]
+    fn test_parse_zone() {
+        assert!(parse_zone("Europe/Berlin").is_ok());
+        assert!(parse_zone("Mars/Olympus").is_err());
+    }
+}
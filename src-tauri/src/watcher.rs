@@ -1,51 +1,433 @@
 use crate::db::DbState;
+use crate::filters::DirFilter;
+use crate::media;
+use crate::mounts;
+use crate::pipeline::{self, Pipeline};
 use crate::scan;
-use notify::RecursiveMode;
-use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
-use std::path::Path;
-use std::sync::mpsc;
-use std::time::Duration;
+use crate::scan::PathEvent;
+use crate::stability;
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
+use notify::{EventKind, PollWatcher, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, new_debouncer_opt, DebounceEventResult, DebouncedEvent, NoCache, RecommendedCache};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
-pub fn spawn(app: AppHandle, db: DbState) {
-    std::thread::spawn(move || {
-        let (tx, rx) = mpsc::channel();
+// how often to check whether unplugged watch roots have come back
+const ROOT_POLL: Duration = Duration::from_secs(30);
+// recent errors kept for the status view
+const MAX_ERRORS: usize = 20;
+// between directory walks for polled roots without their own interval
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+// quiet time before a batch of events is handed over
+const DEBOUNCE: Duration = Duration::from_secs(2);
 
-        let mut debouncer = match new_debouncer(Duration::from_secs(2), tx) {
-            Ok(d) => d,
-            Err(e) => { eprintln!("watcher init: {}", e); return; }
-        };
+type Debouncer = notify_debouncer_full::Debouncer<RecommendedWatcher, RecommendedCache>;
+// one per polled root, each walks its own tree on its own interval
+type Poller = notify_debouncer_full::Debouncer<PollWatcher, NoCache>;
+
+enum Msg {
+    Events(DebounceEventResult),
+    // watch dirs changed in the db: re-read them and swap the watched roots
+    Reload,
+    Pause,
+    Resume,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedDir {
+    pub id: String,
+    pub path: String,
+    // root exists and has a watch on it
+    pub online: bool,
+    // native or poll while online
+    pub backend: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherStatus {
+    pub running: bool,
+    pub paused: bool,
+    pub dirs: Vec<WatchedDir>,
+    pub last_event_at: Option<i64>,
+    pub last_event_path: Option<String>,
+    pub errors: Vec<String>,
+}
+
+// lives in AppState. cheap to clone, every call just queues a message for the watcher thread
+#[derive(Clone)]
+pub struct WatcherHandle {
+    tx: Sender<Msg>,
+    status: Arc<Mutex<WatcherStatus>>,
+}
+
+impl WatcherHandle {
+    pub fn reload(&self) {
+        let _ = self.tx.send(Msg::Reload);
+    }
+
+    pub fn pause(&self) {
+        let _ = self.tx.send(Msg::Pause);
+    }
+
+    pub fn resume(&self) {
+        let _ = self.tx.send(Msg::Resume);
+    }
+
+    pub fn status(&self) -> WatcherStatus {
+        self.status.lock().clone()
+    }
+}
+
+struct Root {
+    id: String,
+    filter: DirFilter,
+    mode: String,
+    poll_interval: Duration,
+    // everything a scan of this root depends on; a change means its clips need another look
+    scan_key: String,
+    online: bool,
+    // set while the root is polled instead of watched natively
+    poller: Option<Poller>,
+}
+
+impl Root {
+    fn backend(&self) -> Option<&'static str> {
+        match (self.online, &self.poller) {
+            (false, _) => None,
+            (true, Some(_)) => Some("poll"),
+            (true, None) => Some("native"),
+        }
+    }
+}
+
+fn log_error(status: &Mutex<WatcherStatus>, msg: String) {
+    eprintln!("watcher: {}", msg);
+    let mut status = status.lock();
+    status.errors.push(format!("{} {}", chrono::Local::now().format("%H:%M:%S"), msg));
+    let excess = status.errors.len().saturating_sub(MAX_ERRORS);
+    status.errors.drain(..excess);
+}
+
+fn recursive_mode(filter: &DirFilter) -> RecursiveMode {
+    if filter.recursive() { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive }
+}
+
+fn poll_root(root: &mut Root, tx: &Sender<Msg>) -> Result<(), String> {
+    let tx = tx.clone();
+    let config = notify::Config::default().with_poll_interval(root.poll_interval);
+    let mut poller: Poller = new_debouncer_opt(DEBOUNCE, None, move |res| { let _ = tx.send(Msg::Events(res)); }, NoCache, config)
+        .map_err(|e| e.to_string())?;
+    poller.watch(root.filter.root(), recursive_mode(&root.filter)).map_err(|e| e.to_string())?;
+    root.poller = Some(poller);
+    Ok(())
+}
+
+// native events unless the dir asks for polling. in auto mode network and fuse
+// mounts are polled up front, and anything the native backend refuses falls back
+fn watch_root(debouncer: &mut Debouncer, root: &mut Root, tx: &Sender<Msg>, status: &Mutex<WatcherStatus>) -> bool {
+    let path = root.filter.root().to_path_buf();
+    let poll = root.mode == "poll" || (root.mode == "auto" && mounts::is_remote(&path));
+    if !poll {
+        match debouncer.watch(&path, recursive_mode(&root.filter)) {
+            Ok(()) => return true,
+            Err(e) if root.mode == "auto" => {
+                log_error(status, format!("watch {}: {}, polling instead", path.display(), e));
+            }
+            Err(e) => {
+                log_error(status, format!("watch {}: {}", path.display(), e));
+                return false;
+            }
+        }
+    }
+    match poll_root(root, tx) {
+        Ok(()) => true,
+        Err(e) => { log_error(status, format!("poll {}: {}", path.display(), e)); false }
+    }
+}
+
+fn unwatch_root(debouncer: &mut Debouncer, root: &mut Root) {
+    // dropping the poller stops its thread
+    if root.poller.take().is_none() {
+        let _ = debouncer.unwatch(root.filter.root());
+    }
+    root.online = false;
+}
+
+// same depth/exclude rules as the scanner
+fn load_roots(db: &DbState, status: &Mutex<WatcherStatus>) -> Vec<Root> {
+    let dirs = match db.get_watch_dirs() {
+        Ok(d) => d,
+        Err(e) => { log_error(status, format!("load watch dirs: {}", e)); return vec![]; }
+    };
+    dirs.iter()
+        .filter(|d| d.enabled)
+        .filter_map(|d| match DirFilter::new(d) {
+            Ok(filter) => Some(Root {
+                id: d.id.clone(),
+                filter,
+                mode: d.watch_mode.clone(),
+                poll_interval: d.poll_interval_secs
+                    .map(|s| Duration::from_secs(s.max(1) as u64))
+                    .unwrap_or(DEFAULT_POLL_INTERVAL),
+                scan_key: format!("{:?}", (&d.path, d.max_depth, &d.include_globs, &d.exclude_globs, &d.profile_id, &d.timezone)),
+                online: false,
+                poller: None,
+            }),
+            Err(e) => { log_error(status, format!("watch {}: {}", d.path, e)); None }
+        })
+        .collect()
+}
+
+fn unwatch_all(debouncer: &mut Debouncer, roots: &mut [Root]) {
+    for root in roots.iter_mut().filter(|r| r.online) {
+        unwatch_root(debouncer, root);
+    }
+}
 
-        let dirs = scan::get_watch_dirs(&db);
-        for dir in &dirs {
-            let p = Path::new(dir);
-            if p.exists() {
-                if let Err(e) = debouncer.watcher().watch(p, RecursiveMode::Recursive) {
-                    eprintln!("watch {}: {}", dir, e);
+fn watch_all(debouncer: &mut Debouncer, roots: &mut [Root], tx: &Sender<Msg>, status: &Mutex<WatcherStatus>) {
+    for root in roots.iter_mut() {
+        if root.filter.root().is_dir() {
+            root.online = watch_root(debouncer, root, tx, status);
+        }
+    }
+}
+
+fn publish_dirs(status: &Mutex<WatcherStatus>, roots: &[Root]) {
+    status.lock().dirs = roots.iter()
+        .map(|r| WatchedDir {
+            id: r.id.clone(),
+            path: r.filter.root().to_string_lossy().to_string(),
+            online: r.online,
+            backend: r.backend().map(String::from),
+        })
+        .collect();
+}
+
+// narrow raw notify events down to video paths under a watch root. renames keep both
+// ends so the clip can be relinked in place; anything else is re-checked on disk.
+// folders count too: a removed or renamed one may hold clips, a new one may hold videos
+fn path_events(events: &[DebouncedEvent], roots: &[Root]) -> Vec<PathEvent> {
+    let relevant = |p: &Path| {
+        if media::is_video(p) {
+            roots.iter().any(|r| r.filter.allows_file(p))
+        } else {
+            // a removed folder can't be told apart from any other removed path
+            roots.iter().any(|r| p.starts_with(r.filter.root()) && p != r.filter.root())
+                && (p.is_dir() || !p.exists())
+        }
+    };
+    let mut upserted = HashSet::new();
+    let mut out = vec![];
+    for event in events {
+        match (&event.kind, event.paths.as_slice()) {
+            (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [from, to]) => {
+                if relevant(from) || relevant(to) {
+                    out.push(PathEvent::Rename(from.clone(), to.clone()));
                 }
             }
+            (EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)), paths) => {
+                out.extend(paths.iter().filter(|p| relevant(p)).map(|p| PathEvent::Remove(p.clone())));
+            }
+            // reads don't change anything, a writer closing the file does
+            (EventKind::Access(kind), _) if *kind != AccessKind::Close(AccessMode::Write) => {}
+            (kind, paths) => {
+                // a folder's own modify events fire for every change inside it; only a
+                // new or moved-in folder needs walking
+                let arrived = matches!(kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_)));
+                for p in paths.iter().filter(|p| relevant(p) && (arrived || !p.is_dir())) {
+                    if upserted.insert(p.clone()) {
+                        out.push(PathEvent::Upsert(p.clone()));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+// update only the touched clips; the frontend patches its list from the payloads.
+// placeholders still being written are remembered so they can be polled until they settle
+fn apply_events(app: &AppHandle, db: &DbState, ffprobe_path: &str, pipeline: &Pipeline, status: &Mutex<WatcherStatus>, events: &[PathEvent], recording: &mut HashSet<PathBuf>) {
+    match scan::scan_paths(db, ffprobe_path, events) {
+        Ok(changed) => {
+            for change in changed {
+                let clip = change.clip();
+                if clip.status == "recording" {
+                    recording.insert(PathBuf::from(&clip.path));
+                } else {
+                    recording.remove(Path::new(&clip.path));
+                }
+                if pipeline::needs_work(clip) {
+                    pipeline.enqueue(&clip.id);
+                }
+                eprintln!("watcher: {} {}", change.event(), clip.path);
+                let _ = app.emit(change.event(), clip);
+            }
+        }
+        Err(e) => log_error(status, format!("update clips: {}", e)),
+    }
+}
+
+fn rescan(app: &AppHandle, db: &DbState, ffprobe_path: &str, pipeline: &Pipeline, status: &Mutex<WatcherStatus>) {
+    match scan::scan_dirs(db, ffprobe_path, &|p| { let _ = app.emit("scan-progress", p); }) {
+        Ok(report) => {
+            let _ = app.emit("clips-updated", report);
+            pipeline.enqueue_pending(db);
+        }
+        Err(e) => log_error(status, format!("rescan: {}", e)),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_events(app: &AppHandle, db: &DbState, ffprobe_path: &str, pipeline: &Pipeline, status: &Mutex<WatcherStatus>, events: &[DebouncedEvent], roots: &[Root], recording: &mut HashSet<PathBuf>) {
+    let changes = path_events(events, roots);
+    let Some(last) = changes.last() else { return };
+    {
+        let path = match last {
+            PathEvent::Upsert(p) | PathEvent::Remove(p) | PathEvent::Rename(_, p) => p,
+        };
+        let mut status = status.lock();
+        status.last_event_at = Some(chrono::Utc::now().timestamp());
+        status.last_event_path = Some(path.to_string_lossy().to_string());
+    }
+    apply_events(app, db, ffprobe_path, pipeline, status, &changes, recording);
+}
+
+// removable drives and shares come and go
+#[allow(clippy::too_many_arguments)]
+fn check_roots(app: &AppHandle, db: &DbState, ffprobe_path: &str, pipeline: &Pipeline, status: &Mutex<WatcherStatus>, debouncer: &mut Debouncer, roots: &mut [Root], tx: &Sender<Msg>) {
+    let mut changed = false;
+    for root in roots.iter_mut() {
+        let exists = root.filter.root().is_dir();
+        if exists == root.online { continue; }
+        changed = true;
+        if exists {
+            eprintln!("watcher: {} is back", root.filter.root().display());
+            root.online = watch_root(debouncer, root, tx, status);
+        } else {
+            eprintln!("watcher: {} went offline", root.filter.root().display());
+            unwatch_root(debouncer, root);
         }
+    }
+    // flips clips between online/offline
+    if changed {
+        publish_dirs(status, roots);
+        rescan(app, db, ffprobe_path, pipeline, status);
+    }
+}
 
-        eprintln!("file watcher active on {} dirs", dirs.len());
+pub fn spawn(app: AppHandle, db: DbState, ffprobe_path: String, pipeline: Pipeline) -> WatcherHandle {
+    let (tx, rx) = mpsc::channel();
+    let status = Arc::new(Mutex::new(WatcherStatus::default()));
+    let handle = WatcherHandle { tx: tx.clone(), status: status.clone() };
 
-        for events in rx {
-            let events = match events {
-                Ok(evts) => evts,
-                Err(e) => { eprintln!("watcher error: {:?}", e); continue; }
-            };
+    std::thread::spawn(move || {
+        let events_tx = tx.clone();
+        let mut debouncer = match new_debouncer(DEBOUNCE, None, move |res| { let _ = events_tx.send(Msg::Events(res)); }) {
+            Ok(d) => d,
+            Err(e) => { log_error(&status, format!("init: {}", e)); return; }
+        };
 
-            // check if any event is an mp4 file change
-            let has_mp4 = events.iter().any(|e| {
-                e.kind == DebouncedEventKind::Any &&
-                e.path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("mp4"))
-            });
+        let mut roots = load_roots(&db, &status);
+        watch_all(&mut debouncer, &mut roots, &tx, &status);
+        publish_dirs(&status, &roots);
+        status.lock().running = true;
+        eprintln!("file watcher active on {} dirs", roots.len());
 
-            if has_mp4 {
-                eprintln!("watcher: mp4 change detected, rescanning");
-                if let Ok(clips) = scan::scan_dirs(&db) {
-                    let _ = app.emit("clips-updated", clips.len());
+        // placeholders left over from a scan or a previous run
+        let mut recording: HashSet<PathBuf> = db.get_all_clips().unwrap_or_default().into_iter()
+            .filter(|c| c.status == "recording")
+            .map(|c| PathBuf::from(c.path))
+            .collect();
+        // both checks are due on a clock, not only when the channel goes quiet: a busy
+        // folder sends events more often than SETTLE and would starve them otherwise
+        let mut next_settle_poll = Instant::now() + stability::SETTLE;
+        let mut last_root_check = Instant::now();
+        let mut paused = false;
+        loop {
+            let mut deadline = last_root_check + ROOT_POLL;
+            if !recording.is_empty() {
+                deadline = deadline.min(next_settle_poll);
+            }
+            // nothing is due while paused, just wait for the next message
+            let timeout = if paused { ROOT_POLL } else { deadline.saturating_duration_since(Instant::now()) };
+            match rx.recv_timeout(timeout) {
+                Ok(Msg::Events(Ok(events))) => {
+                    // events already queued when pausing are dropped
+                    if !paused {
+                        handle_events(&app, &db, &ffprobe_path, &pipeline, &status, &events, &roots, &mut recording);
+                    }
                 }
+                Ok(Msg::Events(Err(errors))) => {
+                    for e in errors {
+                        log_error(&status, e.to_string());
+                    }
+                }
+                Ok(Msg::Reload) => {
+                    let before: Vec<(String, String)> = roots.iter().map(|r| (r.id.clone(), r.scan_key.clone())).collect();
+                    unwatch_all(&mut debouncer, &mut roots);
+                    roots = load_roots(&db, &status);
+                    if !paused {
+                        watch_all(&mut debouncer, &mut roots, &tx, &status);
+                    }
+                    publish_dirs(&status, &roots);
+                    eprintln!("watcher: reloaded, {} dirs", roots.len());
+                    // a new root needs its clips picked up, a removed one its clips marked, and an
+                    // edited one (depth, globs, profile, timezone) its clips re-matched
+                    if !paused && roots.iter().map(|r| (&r.id, &r.scan_key)).ne(before.iter().map(|(id, key)| (id, key))) {
+                        rescan(&app, &db, &ffprobe_path, &pipeline, &status);
+                    }
+                }
+                Ok(Msg::Pause) => {
+                    if !paused {
+                        paused = true;
+                        unwatch_all(&mut debouncer, &mut roots);
+                        publish_dirs(&status, &roots);
+                        status.lock().paused = true;
+                        eprintln!("watcher: paused");
+                    }
+                }
+                Ok(Msg::Resume) => {
+                    if paused {
+                        paused = false;
+                        watch_all(&mut debouncer, &mut roots, &tx, &status);
+                        publish_dirs(&status, &roots);
+                        status.lock().paused = false;
+                        eprintln!("watcher: resumed");
+                        // catch up on whatever changed while nobody was looking
+                        rescan(&app, &db, &ffprobe_path, &pipeline, &status);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            if paused { continue; }
+
+            // a writer that went quiet sends no more events, so poll placeholders. the
+            // clock only starts once there is one to poll
+            if recording.is_empty() {
+                next_settle_poll = Instant::now() + stability::SETTLE;
+            } else if Instant::now() >= next_settle_poll {
+                let pending: Vec<PathEvent> = recording.iter().cloned().map(PathEvent::Upsert).collect();
+                apply_events(&app, &db, &ffprobe_path, &pipeline, &status, &pending, &mut recording);
+                next_settle_poll = Instant::now() + stability::SETTLE;
+            }
+
+            if last_root_check.elapsed() >= ROOT_POLL {
+                last_root_check = Instant::now();
+                check_roots(&app, &db, &ffprobe_path, &pipeline, &status, &mut debouncer, &mut roots, &tx);
             }
         }
+        status.lock().running = false;
     });
+
+    handle
 }
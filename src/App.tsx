@@ -4,7 +4,7 @@ import { listen } from "@tauri-apps/api/event";
 import { check, type Update } from "@tauri-apps/plugin-updater";
 import { relaunch } from "@tauri-apps/plugin-process";
 import { useClipStore, useUiStore, useTagStore, useSearchStore, useCollectionStore } from "./store";
-import type { Clip, Tag, ScanProgress, SearchResult, Collection, SmartFolder, SmartFolderRule } from "./types";
+import type { Clip, Tag, ScanProgress, ScanReport, SearchResult, Collection, SmartFolder, SmartFolderRule, Thumbnail, HoverPreview } from "./types";
 import { evaluateSmartFolder } from "./utils";
 import TitleBar from "./components/TitleBar";
 import Toolbar from "./components/Toolbar";
@@ -34,6 +34,7 @@ export default function App() {
   const setClips = useClipStore((s) => s.setClips);
   const setLoading = useClipStore((s) => s.setLoading);
   const setScanProgress = useClipStore((s) => s.setScanProgress);
+  const setLastScanReport = useClipStore((s) => s.setLastScanReport);
   const viewMode = useUiStore((s) => s.viewMode);
   const sort = useUiStore((s) => s.sort);
   const filter = useUiStore((s) => s.filter);
@@ -56,6 +57,7 @@ export default function App() {
 
   const ffmpegMissing = useUiStore((s) => s.ffmpegMissing);
   const setFfmpegMissing = useUiStore((s) => s.setFfmpegMissing);
+  const setHoverPreview = useUiStore((s) => s.setHoverPreview);
   const [updateAvailable, setUpdateAvailable] = useState<Update | null>(null);
   const [updating, setUpdating] = useState(false);
 
@@ -67,16 +69,23 @@ export default function App() {
         const hasFFmpeg = await invoke<boolean>("check_ffmpeg").catch(() => false);
         if (!hasFFmpeg) setFfmpegMissing(true);
 
-        const [scanned, tags] = await Promise.all([
-          invoke<Clip[]>("scan_clips"),
+        const [report, tags, settings] = await Promise.all([
+          invoke<ScanReport>("scan_clips"),
           invoke<Tag[]>("get_tags"),
+          invoke<{ hoverPreview: HoverPreview }>("get_settings"),
         ]);
-        setClips(scanned);
+        setLastScanReport(report);
+        setHoverPreview(settings.hoverPreview);
+        setClips(await invoke<Clip[]>("get_clips"));
         setTags(tags);
 
         // kick off thumbnail generation in background (only if ffmpeg is available)
+        // then perceptual hashes for "similar clips", one ffmpeg job at a time
         if (hasFFmpeg) {
-          invoke("gen_all_thumbs").catch((e) => console.warn("thumbs:", e));
+          invoke("gen_all_thumbs")
+            .catch((e) => console.warn("thumbs:", e))
+            .then(() => invoke("compute_phashes"))
+            .catch((e) => console.warn("phashes:", e));
         }
       } catch (e) {
         console.warn("init:", e);
@@ -116,22 +125,39 @@ export default function App() {
 
   // listen for thumb updates
   useEffect(() => {
-    const unlisten = listen<{ clipId: string; thumbPath: string }>("thumb-ready", (e) => {
-      const { clipId, thumbPath } = e.payload;
-      useClipStore.getState().updateClip(clipId, { thumbPath });
+    const unlisten = listen<Thumbnail>("thumb-ready", (e) => {
+      const { clipId, ...thumb } = e.payload;
+      useClipStore.getState().updateClip(clipId, thumb);
+    });
+    return () => { unlisten.then((f) => f()); };
+  }, []);
+
+  // probe results from the background pipeline
+  useEffect(() => {
+    const unlisten = listen<{ clipId: string; durationSecs: number; width: number; height: number }>("meta-ready", (e) => {
+      const { clipId, ...meta } = e.payload;
+      useClipStore.getState().updateClip(clipId, meta);
     });
     return () => { unlisten.then((f) => f()); };
   }, []);
 
   // listen for watcher-triggered rescan
   useEffect(() => {
-    const unlisten = listen("clips-updated", async () => {
-      const clips = await invoke<Clip[]>("get_clips");
-      setClips(clips);
+    const unlisten = listen<ScanReport>("clips-updated", async (e) => {
+      setLastScanReport(e.payload);
+      setClips(await invoke<Clip[]>("get_clips"));
     });
     return () => { unlisten.then((f) => f()); };
   }, []); // eslint-disable-line react-hooks/exhaustive-deps
 
+  // watcher updates for single files
+  useEffect(() => {
+    // thumbnails and probe results follow from the backend pipeline
+    const onClip = (e: { payload: Clip }) => useClipStore.getState().upsertClip(e.payload);
+    const unlisteners = ["clip-added", "clip-changed", "clip-removed"].map((name) => listen<Clip>(name, onClip));
+    return () => { unlisteners.forEach((u) => u.then((f) => f())); };
+  }, []);
+
   // load collection clip IDs when active collection changes
   useEffect(() => {
     if (!activeCollectionId || activeCollectionId === "__starred") {
@@ -303,10 +329,12 @@ export default function App() {
           {scanProgress && (
             <div>
               <div className="progress-bar-wrap">
-                <div className="progress-bar-fill" style={{ width: `${(scanProgress.done / scanProgress.total) * 100}%` }} />
+                <div className="progress-bar-fill" style={{ width: `${scanProgress.total ? (scanProgress.done / scanProgress.total) * 100 : 0}%` }} />
               </div>
               <div style={{ fontSize: 11, color: "var(--text-dim)", marginTop: 6, textAlign: "center" }}>
-                {scanProgress.done} / {scanProgress.total}
+                {scanProgress.phase === "scanning"
+                  ? `${scanProgress.filesSeen ?? 0} files · ${scanProgress.dir ?? ""}`
+                  : `${scanProgress.done} / ${scanProgress.total}`}
               </div>
             </div>
           )}
@@ -1,9 +1,10 @@
 import { memo, useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useUiStore, useClipStore, useTagStore } from "../store";
-import { localUrl, fmtSize } from "../utils";
+import { detailThumbUrl, fmtSize } from "../utils";
 import TagManager from "./TagManager";
 import Waveform from "./Waveform";
+import type { Clip, SimilarClip, Thumbnail } from "../types";
 
 const fmtDetailDate = (ts: number) => {
   const d = new Date(ts * 1000);
@@ -30,17 +31,23 @@ export default memo(function ClipDetail() {
   const setCompressClipId = useUiStore((s) => s.setCompressClipId);
   const clips = useClipStore((s) => s.clips);
   const updateClip = useClipStore((s) => s.updateClip);
+  const setClips = useClipStore((s) => s.setClips);
 
   const clip = clips.find((c) => c.id === detailClipId);
   const [desc, setDesc] = useState("");
+  const [similar, setSimilar] = useState<SimilarClip[] | null>(null);
+  const [findingSimilar, setFindingSimilar] = useState(false);
+  const [writingMeta, setWritingMeta] = useState(false);
+  const [regenThumb, setRegenThumb] = useState(false);
 
   useEffect(() => {
     if (clip) setDesc(clip.description);
+    setSimilar(null);
   }, [clip?.id]); // eslint-disable-line react-hooks/exhaustive-deps
 
   if (!clip) return null;
 
-  const thumbSrc = clip.thumbPath ? localUrl(clip.thumbPath) : null;
+  const thumbSrc = detailThumbUrl(clip);
 
   const saveDesc = async () => {
     if (desc === clip.description) return;
@@ -52,6 +59,40 @@ export default memo(function ClipDetail() {
     }
   };
 
+  const findSimilar = async () => {
+    setFindingSimilar(true);
+    try {
+      setSimilar(await invoke<SimilarClip[]>("find_similar_clips", { clipId: clip.id }));
+    } catch (e) {
+      console.warn("find similar:", e);
+    }
+    setFindingSimilar(false);
+  };
+
+  // rewrites the file's container tags; size changes so the list is reloaded
+  const writeMetadata = async () => {
+    setWritingMeta(true);
+    try {
+      await saveDesc();
+      await invoke<number>("write_clip_metadata", { clipIds: [clip.id] });
+      setClips(await invoke<Clip[]>("get_clips"));
+    } catch (e) {
+      console.warn("write metadata:", e);
+    }
+    setWritingMeta(false);
+  };
+
+  const newThumb = async () => {
+    setRegenThumb(true);
+    try {
+      const { clipId, ...thumb } = await invoke<Thumbnail>("regen_thumb", { clipId: clip.id, mode: "smart" });
+      updateClip(clipId, { ...thumb, coverTime: null });
+    } catch (e) {
+      console.warn("regen thumb:", e);
+    }
+    setRegenThumb(false);
+  };
+
   return (
     <div className="detail-panel">
       <div className="detail-header">
@@ -123,7 +164,7 @@ export default memo(function ClipDetail() {
 
       <div className="detail-section">
         <div className="detail-label">Source</div>
-        <div className="detail-value">{clip.dirSource === "root" ? "Videos" : "Captures"}</div>
+        <div className="detail-value">{clip.dirSource}</div>
       </div>
 
       <div className="detail-section">
@@ -156,9 +197,48 @@ export default memo(function ClipDetail() {
           <button className="detail-action-btn" onClick={() => setCompressClipId(clip.id)}>
             Compress
           </button>
+          <button
+            className="detail-action-btn"
+            onClick={writeMetadata}
+            disabled={writingMeta || clip.status !== "online"}
+            title="Write tags, description and star into the video file"
+          >
+            {writingMeta ? "Writing..." : "Write Metadata"}
+          </button>
+          <button
+            className="detail-action-btn"
+            onClick={newThumb}
+            disabled={regenThumb || clip.status !== "online"}
+            title={clip.coverTime != null
+              ? `Replace the cover frame at ${clip.coverTime.toFixed(1)}s with an automatic pick`
+              : "Pick a new thumbnail from several frames across the clip"}
+          >
+            {regenThumb ? "Picking..." : "New Thumbnail"}
+          </button>
         </div>
       </div>
 
+      <div className="detail-section">
+        <div className="detail-label">Similar Clips</div>
+        {similar && similar.length === 0 && (
+          <div className="detail-value" style={{ color: "var(--text-dim)" }}>Nothing similar found</div>
+        )}
+        {similar?.map((s) => {
+          const other = clips.find((c) => c.id === s.clipId);
+          if (!other) return null;
+          return (
+            <button key={s.clipId} className="detail-action-btn" style={{ width: "100%", marginTop: 4 }} onClick={() => setDetailClipId(s.clipId)} title={other.path}>
+              {other.filename} · {s.distance === 0 ? "same" : `distance ${s.distance}`}
+            </button>
+          );
+        })}
+        {!similar && (
+          <button className="detail-action-btn" style={{ marginTop: 4 }} onClick={findSimilar} disabled={findingSimilar}>
+            {findingSimilar ? "Comparing..." : "Find similar"}
+          </button>
+        )}
+      </div>
+
       <div className="detail-section">
         <div className="detail-label">Description</div>
         <textarea
@@ -13,6 +13,7 @@ export default memo(function CompressDialog() {
   const [quality, setQuality] = useState("medium");
   const [maxWidth, setMaxWidth] = useState<number | null>(null);
   const [processing, setProcessing] = useState(false);
+  const [inheritMetadata, setInheritMetadata] = useState(true);
 
   if (!clip) return null;
 
@@ -36,7 +37,7 @@ export default memo(function CompressDialog() {
     if (!outPath) return;
     setProcessing(true);
     try {
-      await invoke("compress_clip", { input: clip.path, output: outPath, quality, maxWidth });
+      await invoke("compress_clip", { input: clip.path, output: outPath, quality, maxWidth, inheritMetadata });
     } catch (e) {
       console.warn("compress:", e);
     }
@@ -92,6 +93,13 @@ export default memo(function CompressDialog() {
           </select>
         </div>
 
+        <div className="detail-section">
+          <label className="trim-precise">
+            <input type="checkbox" checked={inheritMetadata} onChange={(e) => setInheritMetadata(e.target.checked)} />
+            <span>Keep tags and description in the file metadata</span>
+          </label>
+        </div>
+
         <div className="detail-section">
           <div className="detail-label">Estimated Output</div>
           <div className="detail-value">~{fmtSize(estimatedSize)}</div>
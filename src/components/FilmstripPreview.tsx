@@ -1,76 +1,61 @@
-import { memo, useRef, useEffect, useState, useCallback } from "react";
+import { memo, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { localUrl } from "../utils";
+import type { SpriteIndex } from "../types";
 
 interface Props {
-  videoPath: string;
+  clipId: string;
+  // bumps when the file changes so a stale sheet isn't reused
+  version: number;
   visible: boolean;
   mouseX: number; // 0-1 proportion across the thumbnail
 }
 
-export default memo(function FilmstripPreview({ videoPath, visible, mouseX }: Props) {
-  const videoRef = useRef<HTMLVideoElement>(null);
-  const canvasRef = useRef<HTMLCanvasElement>(null);
-  const [ready, setReady] = useState(false);
-  const seekTimer = useRef<ReturnType<typeof setTimeout>>();
-  const lastSeek = useRef(0);
+// sheets are generated once by the backend and shared by every card showing the clip
+const sprites = new Map<string, Promise<SpriteIndex | null>>();
+
+const loadSprite = (clipId: string, version: number) => {
+  const key = `${clipId}:${version}`;
+  let sprite = sprites.get(key);
+  if (!sprite) {
+    sprite = invoke<SpriteIndex>("get_sprite", { clipId }).catch((e) => {
+      console.warn("sprite:", e);
+      return null;
+    });
+    sprites.set(key, sprite);
+  }
+  return sprite;
+};
+
+export default memo(function FilmstripPreview({ clipId, version, visible, mouseX }: Props) {
+  const [sprite, setSprite] = useState<SpriteIndex | null>(null);
 
-  const src = localUrl(videoPath);
-
-  // load video metadata
   useEffect(() => {
-    const video = videoRef.current;
-    if (!video || !visible) return;
-    setReady(false);
-    video.src = src;
-    video.load();
-    const onMeta = () => setReady(true);
-    video.addEventListener("loadedmetadata", onMeta);
-    return () => {
-      video.removeEventListener("loadedmetadata", onMeta);
-      video.src = "";
-    };
-  }, [src, visible]);
-
-  // seek + paint on mouse move (debounced ~60ms)
-  const seekAndPaint = useCallback(() => {
-    const video = videoRef.current;
-    const canvas = canvasRef.current;
-    if (!video || !canvas || !ready || video.duration === 0) return;
-
-    const time = Math.max(0, Math.min(video.duration, mouseX * video.duration));
-    // avoid re-seeking to same spot
-    if (Math.abs(time - lastSeek.current) < 0.05) return;
-    lastSeek.current = time;
+    if (!visible) return;
+    let cancelled = false;
+    loadSprite(clipId, version).then((s) => { if (!cancelled) setSprite(s); });
+    return () => { cancelled = true; };
+  }, [clipId, version, visible]);
 
-    if (video.fastSeek) {
-      video.fastSeek(time);
-    } else {
-      video.currentTime = time;
-    }
-
-    const onSeeked = () => {
-      const ctx = canvas.getContext("2d");
-      if (!ctx) return;
-      canvas.width = video.videoWidth;
-      canvas.height = video.videoHeight;
-      ctx.drawImage(video, 0, 0);
-    };
-    video.addEventListener("seeked", onSeeked, { once: true });
-  }, [mouseX, ready]);
-
-  useEffect(() => {
-    if (!visible || !ready) return;
-    clearTimeout(seekTimer.current);
-    seekTimer.current = setTimeout(seekAndPaint, 60);
-    return () => clearTimeout(seekTimer.current);
-  }, [visible, ready, seekAndPaint]);
+  if (!visible || !sprite) return null;
 
-  if (!visible) return null;
+  // scale the whole sheet so one tile fills the card, then slide to the hovered tile
+  const count = sprite.tiles.length;
+  const i = Math.min(count - 1, Math.floor(mouseX * count));
+  const col = i % sprite.columns;
+  const row = Math.floor(i / sprite.columns);
+  const pos = (n: number, of: number) => (of > 1 ? (n / (of - 1)) * 100 : 0);
 
   return (
     <>
-      <video ref={videoRef} style={{ display: "none" }} preload="metadata" muted />
-      <canvas ref={canvasRef} className="filmstrip-canvas" />
+      <div
+        className="filmstrip-canvas"
+        style={{
+          backgroundImage: `url("${localUrl(sprite.imagePath)}?v=${version}")`,
+          backgroundSize: `${sprite.columns * 100}% ${sprite.rows * 100}%`,
+          backgroundPosition: `${pos(col, sprite.columns)}% ${pos(row, sprite.rows)}%`,
+        }}
+      />
       <div className="filmstrip-bar" style={{ width: `${mouseX * 100}%` }} />
     </>
   );
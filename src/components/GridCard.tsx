@@ -1,15 +1,30 @@
 import { memo, useState, useCallback, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useUiStore, useClipStore, useTagStore } from "../store";
-import { localUrl, fmtDuration, fmtDate, fmtSize } from "../utils";
+import { thumbUrl, fmtDuration, fmtDate, fmtSize } from "../utils";
 import { DIR_SOURCE_COLORS, DIR_SOURCE_FALLBACK_COLORS } from "../constants";
 import FilmstripPreview from "./FilmstripPreview";
+import PreviewLoop from "./PreviewLoop";
 import type { Clip } from "../types";
 
 interface Props {
   clip: Clip;
 }
 
+const STATUS_LABELS: Record<Clip["status"], string> = {
+  online: "",
+  offline: "Offline",
+  missing: "Missing",
+  recording: "Recording",
+};
+
+const STATUS_TITLES: Record<Clip["status"], string> = {
+  online: "",
+  offline: "Drive not connected",
+  missing: "File not found",
+  recording: "Still being written, imported once it finishes",
+};
+
 const getSourceColor = (source: string) => {
   const key = source.toLowerCase();
   if (key in DIR_SOURCE_COLORS) return DIR_SOURCE_COLORS[key];
@@ -26,6 +41,7 @@ export default memo(function GridCard({ clip }: Props) {
   const setPreviewClipId = useUiStore((s) => s.setPreviewClipId);
   const setDetailClipId = useUiStore((s) => s.setDetailClipId);
   const toggleClipSelection = useUiStore((s) => s.toggleClipSelection);
+  const hoverPreview = useUiStore((s) => s.hoverPreview);
   const updateClip = useClipStore((s) => s.updateClip);
   const tags = useTagStore((s) => s.tags);
   const [ctx, setCtx] = useState<{ x: number; y: number } | null>(null);
@@ -36,10 +52,11 @@ export default memo(function GridCard({ clip }: Props) {
 
   const selected = selectedClipId === clip.id;
   const multiSelected = selectedClipIds.has(clip.id);
-  const thumbSrc = clip.thumbPath ? localUrl(clip.thumbPath) : null;
+  const thumbSrc = thumbUrl(clip);
   const clipTags = tags.filter((t) => clip.tags.includes(t.id));
   const dur = fmtDuration(clip.durationSecs);
   const sourceColor = getSourceColor(clip.dirSource);
+  const available = clip.status === "online";
 
   const handleClick = (e: React.MouseEvent) => {
     if (e.ctrlKey || e.metaKey) {
@@ -50,7 +67,7 @@ export default memo(function GridCard({ clip }: Props) {
     setDetailClipId(clip.id);
   };
 
-  const handleDblClick = () => setPreviewClipId(clip.id);
+  const handleDblClick = () => { if (available) setPreviewClipId(clip.id); };
   const handleCtx = (e: React.MouseEvent) => {
     e.preventDefault();
     setCtx({ x: e.clientX, y: e.clientY });
@@ -80,7 +97,7 @@ export default memo(function GridCard({ clip }: Props) {
   return (
     <>
       <div
-        className={`grid-card ${selected ? "selected" : ""} ${multiSelected ? "selected-multi" : ""}`}
+        className={`grid-card ${selected ? "selected" : ""} ${multiSelected ? "selected-multi" : ""} ${available ? "" : "unavailable"}`}
         onClick={handleClick}
         onDoubleClick={handleDblClick}
         onContextMenu={handleCtx}
@@ -101,8 +118,15 @@ export default memo(function GridCard({ clip }: Props) {
               </svg>
             </div>
           )}
-          {clip.durationSecs && (
-            <FilmstripPreview videoPath={clip.path} visible={hovered} mouseX={mouseXRatio} />
+          {available && clip.durationSecs && (hoverPreview === "loop" ? (
+            <PreviewLoop clipId={clip.id} version={clip.fileMtime ?? 0} visible={hovered} />
+          ) : (
+            <FilmstripPreview clipId={clip.id} version={clip.fileMtime ?? 0} visible={hovered} mouseX={mouseXRatio} />
+          ))}
+          {!available && (
+            <span className={`grid-status ${clip.status}`} title={STATUS_TITLES[clip.status]}>
+              {STATUS_LABELS[clip.status]}
+            </span>
           )}
         </div>
 
@@ -169,7 +193,7 @@ function CtxMenu({ x, y, clip, onClose }: { x: number; y: number; clip: Clip; on
   return (
     <div style={{ position: "fixed", inset: 0, zIndex: 2000 }} onMouseDown={handleBg}>
       <div className="ctx-menu" style={{ left: x, top: y }}>
-        <button className="ctx-item" onClick={play}>Play</button>
+        <button className="ctx-item" onClick={play} disabled={clip.status !== "online"}>Play</button>
         <button className="ctx-item" onClick={detail}>Details</button>
         <button className="ctx-item" onClick={editTags}>Edit tags</button>
         <div className="ctx-divider" />
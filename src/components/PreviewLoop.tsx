@@ -0,0 +1,50 @@
+import { memo, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { localUrl } from "../utils";
+
+interface Props {
+  clipId: string;
+  // bumps when the file changes so a stale loop isn't reused
+  version: number;
+  visible: boolean;
+}
+
+// loops are encoded once by the backend; remember the path (or the failure) per clip
+const loops = new Map<string, Promise<string | null>>();
+
+const loadLoop = (clipId: string, version: number) => {
+  const key = `${clipId}:${version}`;
+  let loop = loops.get(key);
+  if (!loop) {
+    loop = invoke<string>("get_preview_loop", { clipId }).catch((e) => {
+      console.warn("preview loop:", e);
+      return null;
+    });
+    loops.set(key, loop);
+  }
+  return loop;
+};
+
+export default memo(function PreviewLoop({ clipId, version, visible }: Props) {
+  const [path, setPath] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!visible) return;
+    let cancelled = false;
+    loadLoop(clipId, version).then((p) => { if (!cancelled) setPath(p); });
+    return () => { cancelled = true; };
+  }, [clipId, version, visible]);
+
+  if (!visible || !path) return null;
+
+  return (
+    <video
+      className="filmstrip-canvas"
+      src={`${localUrl(path)}?v=${version}`}
+      autoPlay
+      muted
+      loop
+      playsInline
+    />
+  );
+});
@@ -1,38 +1,230 @@
 import { memo, useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
-import { useUiStore } from "../store";
+import { useUiStore, useClipStore } from "../store";
+import { fmtSize } from "../utils";
+import type { Clip, DuplicateGroup, HoverPreview, IngestReport, SidecarMode, SkippedFile, ThumbJobStatus, ThumbMode, ThumbSettings, WatchDir, WatchMode, WatcherStatus } from "../types";
+
+const DEFAULT_INGEST_TEMPLATE = "{year}/{month}/{source}/{filename}";
+
+const SKIP_REASONS: Record<SkippedFile["reason"], string> = {
+  patternMismatch: "name doesn't match pattern",
+  excluded: "excluded by folder rules",
+  unreadable: "unreadable",
+  duplicate: "duplicate path",
+};
 
 export default memo(function Settings() {
   const setSettingsOpen = useUiStore((s) => s.setSettingsOpen);
-  const [dirs, setDirs] = useState<string[]>([]);
-  const [saving, setSaving] = useState(false);
+  const hoverPreview = useUiStore((s) => s.hoverPreview);
+  const setHoverPreview = useUiStore((s) => s.setHoverPreview);
+  const [dirs, setDirs] = useState<WatchDir[]>([]);
+  const clips = useClipStore((s) => s.clips);
+  const setClips = useClipStore((s) => s.setClips);
+  const report = useClipStore((s) => s.lastScanReport);
+  const [dupes, setDupes] = useState<DuplicateGroup[] | null>(null);
+  const [findingDupes, setFindingDupes] = useState(false);
+  const [ignoredPaths, setIgnoredPaths] = useState<string[]>([]);
+  const [ingestSource, setIngestSource] = useState<string | null>(null);
+  const [ingestLibraryId, setIngestLibraryId] = useState("");
+  const [ingestTemplate, setIngestTemplate] = useState(DEFAULT_INGEST_TEMPLATE);
+  const [ingestMove, setIngestMove] = useState(false);
+  const [ingesting, setIngesting] = useState(false);
+  const [ingestResult, setIngestResult] = useState<IngestReport | string | null>(null);
+  const [sidecarMode, setSidecarMode] = useState<SidecarMode>("off");
+  const [sidecarStatus, setSidecarStatus] = useState<string | null>(null);
+  const [thumbMode, setThumbMode] = useState<ThumbMode>("fixed");
+  const [thumbSettings, setThumbSettings] = useState<ThumbSettings | null>(null);
+  const [thumbJob, setThumbJob] = useState<ThumbJobStatus | null>(null);
+  const [watcher, setWatcher] = useState<WatcherStatus | null>(null);
+  const missingCount = clips.filter((c) => c.status === "missing").length;
+  const offlineCount = clips.filter((c) => c.status === "offline").length;
+
+  useEffect(() => {
+    invoke<WatchDir[]>("get_watch_dirs").then(setDirs).catch(console.warn);
+    invoke<string[]>("get_ignored_paths").then(setIgnoredPaths).catch(console.warn);
+    invoke<{ sidecarMode: SidecarMode; thumbMode: ThumbMode; thumbSettings: ThumbSettings }>("get_settings")
+      .then((s) => {
+        setSidecarMode(s.sidecarMode);
+        setThumbMode(s.thumbMode);
+        setThumbSettings(s.thumbSettings);
+      })
+      .catch(console.warn);
+  }, []);
 
+  // the watcher applies changes on its own thread, so poll while settings are open
   useEffect(() => {
-    invoke<{ watchDirs: string[] }>("get_settings").then((s) => setDirs(s.watchDirs));
+    const refresh = () => {
+      invoke<WatcherStatus>("get_watcher_status").then(setWatcher).catch(console.warn);
+      invoke<ThumbJobStatus>("get_thumb_job_status").then(setThumbJob).catch(console.warn);
+    };
+    refresh();
+    const timer = setInterval(refresh, 2000);
+    return () => clearInterval(timer);
+  }, []);
+
+  const toggleWatcher = useCallback(async (paused: boolean) => {
+    try {
+      await invoke(paused ? "resume_watcher" : "pause_watcher");
+    } catch (e) {
+      console.warn("watcher:", e);
+    }
+  }, []);
+
+  const changeSidecarMode = useCallback(async (mode: SidecarMode) => {
+    try {
+      await invoke("set_sidecar_mode", { mode });
+      setSidecarMode(mode);
+      setSidecarStatus(null);
+    } catch (e) {
+      console.warn("sidecar mode:", e);
+    }
+  }, []);
+
+  const changeThumbMode = useCallback(async (mode: ThumbMode) => {
+    try {
+      await invoke("set_thumb_mode", { mode });
+      setThumbMode(mode);
+    } catch (e) {
+      console.warn("thumbnail mode:", e);
+    }
+  }, []);
+
+  const changeHoverPreview = useCallback(async (mode: HoverPreview) => {
+    try {
+      await invoke("set_hover_preview", { mode });
+      setHoverPreview(mode);
+    } catch (e) {
+      console.warn("hover preview:", e);
+    }
+  }, [setHoverPreview]);
+
+  const changeThumbSettings = useCallback(async (settings: ThumbSettings) => {
+    setThumbSettings(settings);
+    try {
+      await invoke("set_thumb_settings", { settings });
+    } catch (e) {
+      console.warn("thumbnail settings:", e);
+    }
+  }, []);
+
+  const exportSidecars = useCallback(async () => {
+    try {
+      const n = await invoke<number>("export_sidecars");
+      setSidecarStatus(`Wrote ${n} sidecar files`);
+    } catch (e) {
+      setSidecarStatus(String(e));
+    }
   }, []);
 
+  const importSidecars = useCallback(async () => {
+    try {
+      const n = await invoke<number>("import_sidecars");
+      setSidecarStatus(`Read ${n} sidecar files`);
+      setClips(await invoke<Clip[]>("get_clips"));
+    } catch (e) {
+      setSidecarStatus(String(e));
+    }
+  }, [setClips]);
+
   const addDir = useCallback(async () => {
     const selected = await open({ directory: true, multiple: false });
     if (selected && typeof selected === "string") {
-      setDirs((prev) => (prev.includes(selected) ? prev : [...prev, selected]));
+      try {
+        const dir = await invoke<WatchDir>("add_watch_dir", { path: selected });
+        setDirs((prev) => [...prev, dir]);
+      } catch (e) {
+        console.warn("add watch dir:", e);
+      }
+    }
+  }, []);
+
+  const updateDir = useCallback(async (dir: WatchDir) => {
+    try {
+      await invoke("update_watch_dir", { dir });
+      const rezoned = dirs.find((d) => d.id === dir.id)?.timezone !== dir.timezone;
+      setDirs((prev) => prev.map((d) => (d.id === dir.id ? dir : d)));
+      // filename dates under the dir moved with the timezone
+      if (rezoned) setClips(await invoke<Clip[]>("get_clips"));
+    } catch (e) {
+      console.warn("update watch dir:", e);
+    }
+  }, [dirs, setClips]);
+
+  const removeDir = useCallback(async (id: string) => {
+    try {
+      await invoke("remove_watch_dir", { id });
+      setDirs((prev) => prev.filter((d) => d.id !== id));
+      // its clips stay in the library, now marked missing
+      setClips(await invoke<Clip[]>("get_clips"));
+    } catch (e) {
+      console.warn("remove watch dir:", e);
+    }
+  }, [setClips]);
+
+  const purge = useCallback(async (includeOffline: boolean) => {
+    try {
+      await invoke<number>("purge_clips", { includeOffline });
+      setClips(await invoke<Clip[]>("get_clips"));
+    } catch (e) {
+      console.warn("purge clips:", e);
     }
+  }, [setClips]);
+
+  const pickIngestSource = useCallback(async () => {
+    const selected = await open({ directory: true, multiple: false });
+    if (selected && typeof selected === "string") setIngestSource(selected);
   }, []);
 
-  const removeDir = useCallback((dir: string) => {
-    setDirs((prev) => prev.filter((d) => d !== dir));
+  const runIngest = useCallback(async () => {
+    const libraryId = ingestLibraryId || dirs[0]?.id;
+    if (!ingestSource || !libraryId) return;
+    setIngesting(true);
+    setIngestResult(null);
+    try {
+      const result = await invoke<IngestReport>("ingest_clips", {
+        options: { source: ingestSource, libraryId, template: ingestTemplate, moveFiles: ingestMove },
+      });
+      setIngestResult(result);
+      setClips(await invoke<Clip[]>("get_clips"));
+      invoke("gen_all_thumbs").catch((e) => console.warn("thumbs:", e));
+    } catch (e) {
+      setIngestResult(String(e));
+    }
+    setIngesting(false);
+  }, [ingestSource, ingestLibraryId, ingestTemplate, ingestMove, dirs, setClips]);
+
+  const findDupes = useCallback(async () => {
+    setFindingDupes(true);
+    try {
+      setDupes(await invoke<DuplicateGroup[]>("find_duplicates"));
+    } catch (e) {
+      console.warn("find duplicates:", e);
+    }
+    setFindingDupes(false);
   }, []);
 
-  const save = useCallback(async () => {
-    setSaving(true);
+  const resolveGroup = useCallback(async (group: DuplicateGroup, deleteFiles: boolean) => {
+    const [keepId, ...dropIds] = group.clipIds;
     try {
-      await invoke("set_watch_dirs", { dirs });
+      await invoke("resolve_duplicates", { keepId, dropIds, deleteFiles });
+      setDupes((prev) => prev?.filter((g) => g.contentHash !== group.contentHash) ?? null);
+      setClips(await invoke<Clip[]>("get_clips"));
+      setIgnoredPaths(await invoke<string[]>("get_ignored_paths"));
     } catch (e) {
-      console.warn("save settings:", e);
+      console.warn("resolve duplicates:", e);
     }
-    setSaving(false);
-    setSettingsOpen(false);
-  }, [dirs, setSettingsOpen]);
+  }, [setClips]);
+
+  // omit paths to clear them all
+  const unignore = useCallback(async (paths?: string[]) => {
+    try {
+      await invoke("clear_ignored_paths", { paths: paths ?? null });
+      setIgnoredPaths(await invoke<string[]>("get_ignored_paths"));
+    } catch (e) {
+      console.warn("clear ignored paths:", e);
+    }
+  }, []);
 
   return (
     <div className="settings-overlay" onClick={() => setSettingsOpen(false)}>
@@ -50,9 +242,35 @@ export default memo(function Settings() {
           <div className="detail-label">Watch Directories</div>
           <div className="settings-dirs">
             {dirs.map((dir) => (
-              <div key={dir} className="settings-dir-item">
-                <span className="settings-dir-path">{dir}</span>
-                <button className="settings-dir-remove" onClick={() => removeDir(dir)}>
+              <div key={dir.id} className="settings-dir-item">
+                <span className="settings-dir-color" style={{ background: dir.color }} />
+                <span className="settings-dir-path" title={dir.path}>{dir.label} · {dir.path}</span>
+                <select
+                  className="smart-rule-select"
+                  value={dir.watchMode}
+                  onChange={(e) => updateDir({ ...dir, watchMode: e.target.value as WatchMode })}
+                  title="Network shares don't report changes made by other machines; polling rescans them on an interval"
+                >
+                  <option value="auto">Auto</option>
+                  <option value="native">Native events</option>
+                  <option value="poll">Poll</option>
+                </select>
+                {dir.watchMode !== "native" && (
+                  <input
+                    className="tag-input"
+                    type="number"
+                    min={1}
+                    style={{ width: 56 }}
+                    placeholder="10"
+                    title="Seconds between polls"
+                    value={dir.pollIntervalSecs ?? ""}
+                    onChange={(e) => {
+                      const secs = parseInt(e.target.value, 10);
+                      updateDir({ ...dir, pollIntervalSecs: secs > 0 ? secs : null });
+                    }}
+                  />
+                )}
+                <button className="settings-dir-remove" onClick={() => removeDir(dir.id)}>
                   <svg width="12" height="12" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round">
                     <path d="M18 6L6 18M6 6l12 12" />
                   </svg>
@@ -61,7 +279,7 @@ export default memo(function Settings() {
             ))}
             {dirs.length === 0 && (
               <div style={{ fontSize: 12, color: "var(--text-dim)", padding: "8px 0" }}>
-                No directories configured. Default Videos folder will be used.
+                No directories configured.
               </div>
             )}
           </div>
@@ -73,12 +291,283 @@ export default memo(function Settings() {
           </button>
         </div>
 
+        {watcher && (
+          <div className="detail-section">
+            <div className="detail-label">File Watcher</div>
+            <div style={{ fontSize: 12, color: "var(--text-dim)", padding: "4px 0" }}>
+              {!watcher.running ? "Not running" : watcher.paused ? "Paused" : `Watching ${watcher.dirs.filter((d) => d.online).length} of ${watcher.dirs.length} directories`}
+              {watcher.lastEventAt && (
+                <> · last change {new Date(watcher.lastEventAt * 1000).toLocaleTimeString()}</>
+              )}
+            </div>
+            {watcher.dirs.filter((d) => !d.online && !watcher.paused).map((d) => (
+              <div key={d.id} style={{ fontSize: 12, color: "var(--text-dim)" }} title={d.path}>
+                {d.path} · unavailable
+              </div>
+            ))}
+            {watcher.dirs.filter((d) => d.backend === "poll").map((d) => (
+              <div key={d.id} style={{ fontSize: 12, color: "var(--text-dim)" }} title={d.path}>
+                {d.path} · polling
+              </div>
+            ))}
+            {watcher.errors.length > 0 && (
+              <div className="settings-dirs" style={{ maxHeight: 100, overflowY: "auto" }}>
+                {watcher.errors.map((e, i) => (
+                  <div key={i} style={{ fontSize: 11, color: "#f87171" }}>{e}</div>
+                ))}
+              </div>
+            )}
+            <button className="toolbar-btn" onClick={() => toggleWatcher(watcher.paused)} disabled={!watcher.running} style={{ marginTop: 8 }}>
+              {watcher.paused ? "Resume" : "Pause"}
+            </button>
+          </div>
+        )}
+
+        <div className="detail-section">
+          <div className="detail-label">Thumbnails</div>
+          <select
+            className="smart-rule-select"
+            style={{ marginTop: 4 }}
+            value={thumbMode}
+            onChange={(e) => changeThumbMode(e.target.value as ThumbMode)}
+          >
+            <option value="fixed">Fixed (frame at 2s)</option>
+            <option value="smart">Smart (skip black frames and loading screens)</option>
+          </select>
+          <div style={{ fontSize: 11, color: "var(--text-dim)", padding: "4px 0 0" }}>
+            Smart decodes several frames per clip, so thumbnails take noticeably longer to generate.
+          </div>
+          <select
+            className="smart-rule-select"
+            style={{ marginTop: 8 }}
+            value={hoverPreview}
+            onChange={(e) => changeHoverPreview(e.target.value as HoverPreview)}
+          >
+            <option value="filmstrip">On hover: scrub filmstrip</option>
+            <option value="loop">On hover: play preview loop</option>
+          </select>
+          {thumbSettings && (
+            <div style={{ display: "flex", gap: 6, marginTop: 8, alignItems: "center", flexWrap: "wrap" }}>
+              <select
+                className="smart-rule-select"
+                value={thumbSettings.format}
+                onChange={(e) => changeThumbSettings({ ...thumbSettings, format: e.target.value as ThumbSettings["format"] })}
+              >
+                <option value="jpeg">JPEG</option>
+                <option value="webp">WebP</option>
+                <option value="avif">AVIF</option>
+              </select>
+              <label style={{ fontSize: 12, color: "var(--text-dim)" }}>
+                Quality
+                <input
+                  className="tag-input"
+                  type="number"
+                  min={1}
+                  max={100}
+                  style={{ width: 56, marginLeft: 4 }}
+                  defaultValue={thumbSettings.quality}
+                  onBlur={(e) => changeThumbSettings({ ...thumbSettings, quality: Number(e.target.value) })}
+                />
+              </label>
+              <label style={{ fontSize: 12, color: "var(--text-dim)" }}>
+                Grid
+                <input
+                  className="tag-input"
+                  type="number"
+                  min={64}
+                  max={3840}
+                  style={{ width: 64, marginLeft: 4 }}
+                  defaultValue={thumbSettings.gridWidth}
+                  onBlur={(e) => changeThumbSettings({ ...thumbSettings, gridWidth: Number(e.target.value) })}
+                />
+              </label>
+              <label style={{ fontSize: 12, color: "var(--text-dim)" }}>
+                Detail
+                <input
+                  className="tag-input"
+                  type="number"
+                  min={64}
+                  max={3840}
+                  style={{ width: 64, marginLeft: 4 }}
+                  defaultValue={thumbSettings.detailWidth}
+                  onBlur={(e) => changeThumbSettings({ ...thumbSettings, detailWidth: Number(e.target.value) })}
+                />
+              </label>
+            </div>
+          )}
+          {thumbJob && (thumbJob.running || thumbJob.stale > 0) && (
+            <div style={{ display: "flex", gap: 8, alignItems: "center", marginTop: 6, fontSize: 12, color: "var(--text-dim)" }}>
+              {thumbJob.running
+                ? `Regenerating ${thumbJob.done} / ${thumbJob.total}`
+                : `${thumbJob.stale} thumbnails out of date`}
+              {!thumbJob.running && (
+                <button className="toolbar-btn" onClick={() => invoke("regen_stale_thumbs").catch(console.warn)}>
+                  Regenerate
+                </button>
+              )}
+            </div>
+          )}
+        </div>
+
+        <div className="detail-section">
+          <div className="detail-label">Sidecar Files</div>
+          <select
+            className="smart-rule-select"
+            style={{ marginTop: 4 }}
+            value={sidecarMode}
+            onChange={(e) => changeSidecarMode(e.target.value as SidecarMode)}
+          >
+            <option value="off">Off (library database only)</option>
+            <option value="json">JSON next to each clip (.boxy.json)</option>
+            <option value="xmp">XMP next to each clip (.xmp)</option>
+          </select>
+          <div style={{ display: "flex", gap: 8, marginTop: 8 }}>
+            <button className="toolbar-btn" onClick={exportSidecars} disabled={sidecarMode === "off"}>Write all</button>
+            <button className="toolbar-btn" onClick={importSidecars}>Read all</button>
+          </div>
+          {sidecarStatus && (
+            <div style={{ fontSize: 12, color: "var(--text-dim)", marginTop: 6 }}>{sidecarStatus}</div>
+          )}
+        </div>
+
+        {dirs.length > 0 && (
+          <div className="detail-section">
+            <div className="detail-label">Import Clips</div>
+            <div style={{ display: "flex", flexDirection: "column", gap: 6, marginTop: 4 }}>
+              <button className="toolbar-btn" onClick={pickIngestSource}>
+                {ingestSource ? ingestSource : "Choose source folder..."}
+              </button>
+              <select
+                className="smart-rule-select"
+                value={ingestLibraryId || dirs[0].id}
+                onChange={(e) => setIngestLibraryId(e.target.value)}
+              >
+                {dirs.map((d) => <option key={d.id} value={d.id}>into {d.label}</option>)}
+              </select>
+              <input
+                className="tag-input"
+                value={ingestTemplate}
+                onChange={(e) => setIngestTemplate(e.target.value)}
+                placeholder={DEFAULT_INGEST_TEMPLATE}
+                title="{year} {month} {day} {source} {filename} {stem} {ext}"
+              />
+              <label style={{ fontSize: 12, color: "var(--text-dim)", display: "flex", gap: 6, alignItems: "center" }}>
+                <input type="checkbox" checked={ingestMove} onChange={(e) => setIngestMove(e.target.checked)} />
+                Move instead of copy
+              </label>
+              <button className="toolbar-btn" onClick={runIngest} disabled={!ingestSource || ingesting}>
+                {ingesting ? "Importing..." : "Import"}
+              </button>
+              {typeof ingestResult === "string" && (
+                <div style={{ fontSize: 12, color: "#f87171" }}>{ingestResult}</div>
+              )}
+              {ingestResult && typeof ingestResult !== "string" && (
+                <div style={{ fontSize: 12, color: "var(--text-dim)" }}>
+                  {ingestResult.imported.length} imported · {ingestResult.skipped.length} skipped · {ingestResult.failed.length} failed
+                </div>
+              )}
+            </div>
+          </div>
+        )}
+
+        {report && (
+          <div className="detail-section">
+            <div className="detail-label">Last Scan</div>
+            <div style={{ fontSize: 12, color: "var(--text-dim)", padding: "4px 0" }}>
+              {report.added.length} added · {report.updated.length} updated · {report.relinked.length} relinked
+              · {report.offline.length} offline · {report.removed.length} removed · {report.recording.length} recording
+              · {report.skipped.length} skipped
+            </div>
+            {report.skipped.length > 0 && (
+              <div className="settings-dirs" style={{ maxHeight: 160, overflowY: "auto" }}>
+                {report.skipped.map((f) => (
+                  <div key={f.path} className="settings-dir-item">
+                    <span className="settings-dir-path" title={f.path}>
+                      {f.path.split(/[\\/]/).pop()} · {SKIP_REASONS[f.reason]}
+                    </span>
+                  </div>
+                ))}
+              </div>
+            )}
+          </div>
+        )}
+
+        <div className="detail-section">
+          <div className="detail-label">Duplicates</div>
+          {dupes && dupes.length === 0 && (
+            <div style={{ fontSize: 12, color: "var(--text-dim)", padding: "4px 0 8px" }}>
+              No identical copies found.
+            </div>
+          )}
+          {dupes && dupes.length > 0 && (
+            <div className="settings-dirs" style={{ maxHeight: 220, overflowY: "auto", marginBottom: 8 }}>
+              {dupes.map((g) => (
+                <div key={g.contentHash} className="settings-dir-item" style={{ flexDirection: "column", alignItems: "stretch" }}>
+                  {g.clipIds.map((id, i) => {
+                    const c = clips.find((x) => x.id === id);
+                    return (
+                      <span key={id} className="settings-dir-path" title={c?.path}>
+                        {i === 0 ? "keep · " : "drop · "}{c?.path ?? id}
+                      </span>
+                    );
+                  })}
+                  <div style={{ display: "flex", gap: 8, marginTop: 4 }}>
+                    <span style={{ fontSize: 11, color: "var(--text-dim)", flex: 1 }}>{fmtSize(g.fileSize)} each</span>
+                    <button className="toolbar-btn" onClick={() => resolveGroup(g, false)}>Merge</button>
+                    <button className="toolbar-btn" onClick={() => resolveGroup(g, true)}>Merge &amp; delete files</button>
+                  </div>
+                </div>
+              ))}
+            </div>
+          )}
+          <button className="toolbar-btn" onClick={findDupes} disabled={findingDupes}>
+            {findingDupes ? "Checking..." : "Find duplicates"}
+          </button>
+          {ignoredPaths.length > 0 && (
+            <>
+              <div style={{ fontSize: 12, color: "var(--text-dim)", padding: "8px 0 4px" }}>
+                Merged copies skipped by scans
+              </div>
+              <div className="settings-dirs" style={{ maxHeight: 160, overflowY: "auto", marginBottom: 8 }}>
+                {ignoredPaths.map((p) => (
+                  <div key={p} className="settings-dir-item">
+                    <span className="settings-dir-path" title={p}>{p}</span>
+                    <button className="settings-dir-remove" onClick={() => unignore([p])} title="Scan this file again">
+                      <svg width="12" height="12" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round">
+                        <path d="M18 6L6 18M6 6l12 12" />
+                      </svg>
+                    </button>
+                  </div>
+                ))}
+              </div>
+              <button className="toolbar-btn" onClick={() => unignore()}>Clear all</button>
+            </>
+          )}
+        </div>
+
+        {(missingCount > 0 || offlineCount > 0) && (
+          <div className="detail-section">
+            <div className="detail-label">Unavailable Clips</div>
+            <div style={{ fontSize: 12, color: "var(--text-dim)", padding: "4px 0 8px" }}>
+              {missingCount} missing · {offlineCount} on disconnected drives
+            </div>
+            <div style={{ display: "flex", gap: 8 }}>
+              <button className="toolbar-btn" onClick={() => purge(false)} disabled={missingCount === 0}>
+                Purge missing
+              </button>
+              <button className="toolbar-btn" onClick={() => purge(true)}>
+                Purge all unavailable
+              </button>
+            </div>
+          </div>
+        )}
+
         <div className="detail-section" style={{ borderBottom: "none" }}>
-          <button className="detail-play-btn" onClick={save} disabled={saving}>
-            {saving ? "Saving..." : "Save & Close"}
+          <button className="detail-play-btn" onClick={() => setSettingsOpen(false)}>
+            Done
           </button>
           <div style={{ fontSize: 11, color: "var(--text-dim)", marginTop: 8, textAlign: "center" }}>
-            Rescan will pick up changes on next launch
+            Removing a directory removes its clips from the library
           </div>
         </div>
       </div>
@@ -1,6 +1,6 @@
 import { memo } from "react";
 import { useUiStore } from "../store";
-import { localUrl, fmtDuration } from "../utils";
+import { thumbUrl, fmtDuration } from "../utils";
 import { DIR_SOURCE_COLORS, DIR_SOURCE_FALLBACK_COLORS } from "../constants";
 import type { Clip } from "../types";
 
@@ -40,7 +40,7 @@ export default memo(function TimelineDay({ date, clips }: Props) {
       </div>
       <div className="timeline-clips">
         {clips.map((clip) => {
-          const thumbSrc = clip.thumbPath ? localUrl(clip.thumbPath) : null;
+          const thumbSrc = thumbUrl(clip);
           const dur = fmtDuration(clip.durationSecs);
           return (
             <div
@@ -15,6 +15,7 @@ export default memo(function TrimEditor() {
   const [start, setStart] = useState(0);
   const [end, setEnd] = useState(0);
   const [precise, setPrecise] = useState(false);
+  const [inheritMetadata, setInheritMetadata] = useState(true);
   const [processing, setProcessing] = useState(false);
   const [mode, setMode] = useState<"trim" | "gif">("trim");
 
@@ -45,7 +46,7 @@ export default memo(function TrimEditor() {
     if (!outPath) return;
     setProcessing(true);
     try {
-      await invoke("trim_clip", { input: clip.path, output: outPath, start, end, precise });
+      await invoke("trim_clip", { input: clip.path, output: outPath, start, end, precise, inheritMetadata });
     } catch (e) {
       console.warn("trim:", e);
     }
@@ -161,6 +162,13 @@ export default memo(function TrimEditor() {
             </label>
           )}
 
+          {mode === "trim" && (
+            <label className="trim-precise">
+              <input type="checkbox" checked={inheritMetadata} onChange={(e) => setInheritMetadata(e.target.checked)} />
+              <span>Keep tags and description in the file metadata</span>
+            </label>
+          )}
+
           {mode === "gif" && (
             <div className="trim-gif-opts">
               <label>
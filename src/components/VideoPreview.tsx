@@ -1,14 +1,20 @@
 import { memo, useEffect, useCallback, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { useUiStore, useClipStore } from "../store";
 import { localUrl, fmtSize } from "../utils";
 import { SPEED_OPTIONS } from "../constants";
+import type { Thumbnail } from "../types";
 
 export default memo(function VideoPreview() {
   const previewClipId = useUiStore((s) => s.previewClipId);
   const setPreviewClipId = useUiStore((s) => s.setPreviewClipId);
   const clips = useClipStore((s) => s.clips);
+  const updateClip = useClipStore((s) => s.updateClip);
   const videoRef = useRef<HTMLVideoElement>(null);
   const [speed, setSpeed] = useState(1);
+  const [videoSrc, setVideoSrc] = useState<string | null>(null);
+  const [settingCover, setSettingCover] = useState(false);
+  const forcedRef = useRef(false);
 
   const clip = clips.find((c) => c.id === previewClipId);
   const idx = clips.findIndex((c) => c.id === previewClipId);
@@ -29,6 +35,39 @@ export default memo(function VideoPreview() {
   // reset speed when clip changes
   useEffect(() => { setSpeed(1); }, [previewClipId]);
 
+  // resolve a playable source (containers the webview can't play get remuxed)
+  useEffect(() => {
+    if (!clip) return;
+    forcedRef.current = false;
+    setVideoSrc(null);
+    invoke<string>("get_playback_path", { clipId: clip.id, videoPath: clip.path, force: false })
+      .then((p) => setVideoSrc(localUrl(p)))
+      .catch(() => setVideoSrc(localUrl(clip.path)));
+  }, [clip?.id, clip?.path]);
+
+  const handleError = useCallback(() => {
+    if (!clip || forcedRef.current) return;
+    forcedRef.current = true;
+    invoke<string>("get_playback_path", { clipId: clip.id, videoPath: clip.path, force: true })
+      .then((p) => setVideoSrc(localUrl(p)))
+      .catch((e) => console.warn("playback fallback:", e));
+  }, [clip]);
+
+  // thumbnail from the frame on screen, kept when thumbnails are regenerated later
+  const setCover = useCallback(async () => {
+    const video = videoRef.current;
+    if (!clip || !video) return;
+    const time = video.currentTime;
+    setSettingCover(true);
+    try {
+      const { clipId, ...thumb } = await invoke<Thumbnail>("set_cover_frame", { clipId: clip.id, time });
+      updateClip(clipId, { ...thumb, coverTime: time });
+    } catch (e) {
+      console.warn("set cover:", e);
+    }
+    setSettingCover(false);
+  }, [clip, updateClip]);
+
   useEffect(() => {
     if (!previewClipId) return;
     const handle = (e: KeyboardEvent) => {
@@ -54,8 +93,6 @@ export default memo(function VideoPreview() {
 
   if (!clip) return null;
 
-  const videoSrc = localUrl(clip.path);
-
   return (
     <div className="preview-overlay" onClick={(e) => { if (e.target === e.currentTarget) close(); }}>
       <button className="preview-close" onClick={close}>
@@ -75,10 +112,11 @@ export default memo(function VideoPreview() {
       <video
         ref={videoRef}
         className="preview-video"
-        src={videoSrc}
+        src={videoSrc ?? undefined}
         controls
         autoPlay
         key={clip.id}
+        onError={handleError}
         onLoadedMetadata={() => {
           if (videoRef.current) videoRef.current.playbackRate = speed;
         }}
@@ -96,6 +134,14 @@ export default memo(function VideoPreview() {
         <span className="preview-filename">{clip.filename}</span>
         <span className="preview-meta">{fmtSize(clip.fileSize)}</span>
         <span className="preview-meta">{idx + 1} / {clips.length}</span>
+        <button
+          className="speed-pill"
+          onClick={setCover}
+          disabled={settingCover || clip.status !== "online"}
+          title="Use the current frame as this clip's thumbnail"
+        >
+          {settingCover ? "Saving..." : "Set Cover"}
+        </button>
         <div className="speed-pills">
           {SPEED_OPTIONS.map((s) => (
             <button
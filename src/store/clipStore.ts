@@ -1,5 +1,5 @@
 import { create } from "zustand";
-import type { Clip, ScanProgress } from "../types";
+import type { Clip, ScanProgress, ScanReport } from "../types";
 
 const toggle = <T,>(v: T | ((p: T) => T), prev: T): T =>
   typeof v === "function" ? (v as (p: T) => T)(prev) : v;
@@ -8,23 +8,35 @@ interface ClipState {
   clips: Clip[];
   loading: boolean;
   scanProgress: ScanProgress | null;
+  lastScanReport: ScanReport | null;
 
   setClips: (v: Clip[] | ((p: Clip[]) => Clip[])) => void;
   setLoading: (v: boolean) => void;
   setScanProgress: (v: ScanProgress | null) => void;
+  setLastScanReport: (v: ScanReport | null) => void;
   updateClip: (id: string, patch: Partial<Clip>) => void;
+  upsertClip: (clip: Clip) => void;
 }
 
 export const useClipStore = create<ClipState>((set) => ({
   clips: [],
   loading: true,
   scanProgress: null,
+  lastScanReport: null,
 
   setClips: (v) => set((s) => ({ clips: toggle(v, s.clips) })),
   setLoading: (v) => set({ loading: v }),
   setScanProgress: (v) => set({ scanProgress: v }),
+  setLastScanReport: (v) => set({ lastScanReport: v }),
   updateClip: (id, patch) =>
     set((s) => ({
       clips: s.clips.map((c) => (c.id === id ? { ...c, ...patch } : c)),
     })),
+  // replace by id, or insert keeping the newest-first order get_clips returns
+  upsertClip: (clip) =>
+    set((s) => {
+      const rest = s.clips.filter((c) => c.id !== clip.id);
+      const at = rest.findIndex((c) => c.recordedAt < clip.recordedAt);
+      return { clips: at === -1 ? [...rest, clip] : [...rest.slice(0, at), clip, ...rest.slice(at)] };
+    }),
 }));
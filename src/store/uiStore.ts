@@ -1,5 +1,5 @@
 import { create } from "zustand";
-import type { ClipFilter, SortConfig, ViewMode } from "../types";
+import type { ClipFilter, HoverPreview, SortConfig, ViewMode } from "../types";
 import { DEFAULT_FILTER, DEFAULT_SORT } from "../constants";
 
 const toggle = (v: boolean | ((p: boolean) => boolean), prev: boolean) =>
@@ -20,6 +20,7 @@ interface UiState {
   trimClipId: string | null;
   compressClipId: string | null;
   ffmpegMissing: boolean;
+  hoverPreview: HoverPreview;
 
   setViewMode: (v: ViewMode) => void;
   setSort: (v: SortConfig) => void;
@@ -34,6 +35,7 @@ interface UiState {
   setTrimClipId: (v: string | null) => void;
   setCompressClipId: (v: string | null) => void;
   setFfmpegMissing: (v: boolean) => void;
+  setHoverPreview: (v: HoverPreview) => void;
   toggleClipSelection: (id: string) => void;
   clearSelection: () => void;
   selectAll: (ids: string[]) => void;
@@ -54,6 +56,7 @@ export const useUiStore = create<UiState>((set) => ({
   trimClipId: null,
   compressClipId: null,
   ffmpegMissing: false,
+  hoverPreview: "filmstrip",
 
   setViewMode: (v) => set({ viewMode: v }),
   setSort: (v) => set({ sort: v }),
@@ -68,6 +71,7 @@ export const useUiStore = create<UiState>((set) => ({
   setTrimClipId: (v) => set({ trimClipId: v }),
   setCompressClipId: (v) => set({ compressClipId: v }),
   setFfmpegMissing: (v) => set({ ffmpegMissing: v }),
+  setHoverPreview: (v) => set({ hoverPreview: v }),
   toggleClipSelection: (id) => set((s) => {
     const next = new Set(s.selectedClipIds);
     if (next.has(id)) next.delete(id);
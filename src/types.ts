@@ -2,16 +2,23 @@ export interface Clip {
   id: string;
   filename: string;
   path: string;
-  dirSource: "root" | "captures";
+  dirSource: string;
+  watchDirId: string | null;
   recordedAt: number;
+  recordedAtSource: "filename" | "metadata" | "filetime" | "manual";
   fileSize: number;
+  fileMtime: number | null;
   durationSecs: number | null;
   width: number | null;
   height: number | null;
   thumbPath: string | null;
+  thumbDetailPath: string | null;
+  coverTime: number | null;
   description: string;
   tags: string[];
   starred: boolean;
+  // offline: watch root unreachable (unplugged drive), missing: file gone from a present root
+  status: "online" | "offline" | "missing" | "recording";
   createdAt: number;
   updatedAt: number;
 }
@@ -43,6 +50,9 @@ export interface SortConfig {
 
 export type ViewMode = "grid" | "timeline";
 
+// what hovering a grid card shows
+export type HoverPreview = "filmstrip" | "loop";
+
 export interface SearchResult {
   clipId: string;
   score: number;
@@ -51,7 +61,88 @@ export interface SearchResult {
 export interface ScanProgress {
   total: number;
   done: number;
-  phase: "scanning" | "thumbnails" | "complete";
+  phase: "scanning" | "thumbnails" | "hashing" | "complete";
+  // scanning only
+  dir?: string;
+  file?: string | null;
+  filesSeen?: number;
+}
+
+export interface SkippedFile {
+  path: string;
+  reason: "patternMismatch" | "excluded" | "unreadable" | "duplicate";
+}
+
+export interface DuplicateGroup {
+  contentHash: string;
+  fileSize: number;
+  // suggested copy to keep comes first
+  clipIds: string[];
+}
+
+export interface SimilarClip {
+  clipId: string;
+  // average hamming distance of the closest frames, 0 = visually identical
+  distance: number;
+}
+
+export interface IngestReport {
+  imported: string[];
+  skipped: SkippedFile[];
+  failed: { path: string; error: string }[];
+}
+
+export type SidecarMode = "off" | "json" | "xmp";
+
+export type ThumbMode = "smart" | "fixed";
+
+export interface ThumbSettings {
+  format: "jpeg" | "webp" | "avif";
+  quality: number;
+  gridWidth: number;
+  detailWidth: number;
+}
+
+export interface ThumbJobStatus {
+  running: boolean;
+  done: number;
+  total: number;
+  stale: number;
+}
+
+export interface SpriteTile {
+  start: number;
+  end: number;
+  x: number;
+  y: number;
+}
+
+export interface SpriteIndex {
+  imagePath: string;
+  vttPath: string;
+  columns: number;
+  rows: number;
+  tileWidth: number;
+  tileHeight: number;
+  interval: number;
+  tiles: SpriteTile[];
+}
+
+export interface Thumbnail {
+  clipId: string;
+  thumbPath: string;
+  thumbDetailPath: string;
+  updatedAt: number;
+}
+
+export interface ScanReport {
+  added: string[];
+  updated: string[];
+  relinked: string[];
+  offline: string[];
+  removed: string[];
+  recording: string[];
+  skipped: SkippedFile[];
 }
 
 export interface ClipMeta {
@@ -86,3 +177,48 @@ export interface SmartFolder {
   createdAt: number;
   updatedAt: number;
 }
+
+export interface PatternProfile {
+  id: string;
+  name: string;
+  regex: string;
+  timestampFormat: string;
+  builtin: boolean;
+  createdAt: number;
+  updatedAt: number;
+}
+
+export type WatchMode = "auto" | "native" | "poll";
+
+export interface WatchDir {
+  id: string;
+  path: string;
+  label: string;
+  color: string;
+  maxDepth: number | null;
+  includeGlobs: string[];
+  excludeGlobs: string[];
+  enabled: boolean;
+  profileId: string | null;
+  timezone: string | null;
+  watchMode: WatchMode;
+  pollIntervalSecs: number | null;
+  createdAt: number;
+  updatedAt: number;
+}
+
+export interface WatchedDir {
+  id: string;
+  path: string;
+  online: boolean;
+  backend: "native" | "poll" | null;
+}
+
+export interface WatcherStatus {
+  running: boolean;
+  paused: boolean;
+  dirs: WatchedDir[];
+  lastEventAt: number | null;
+  lastEventPath: string | null;
+  errors: string[];
+}
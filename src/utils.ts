@@ -5,6 +5,15 @@ export const localUrl = (path: string) => {
   return `http://localfile.localhost/${encodeURIComponent(normalized).replace(/%2F/g, "/")}`;
 };
 
+// thumbnails keep their path when regenerated, bust the cache when the source file
+// changes or the clip is touched (a re-picked thumbnail bumps updatedAt)
+export const thumbUrl = (clip: Clip) =>
+  clip.thumbPath ? `${localUrl(clip.thumbPath)}?v=${clip.fileMtime ?? 0}.${clip.updatedAt}` : null;
+
+// larger variant for the detail panel; thumbnails from before variants only have the grid one
+export const detailThumbUrl = (clip: Clip) =>
+  clip.thumbDetailPath ? `${localUrl(clip.thumbDetailPath)}?v=${clip.fileMtime ?? 0}.${clip.updatedAt}` : thumbUrl(clip);
+
 export const fmtDuration = (secs: number | null) => {
   if (!secs) return null;
   const m = Math.floor(secs / 60);